@@ -5,18 +5,40 @@ use std::{
 
 use bzd_lib::error::Error;
 
+// JSON шлюз и structured режим эвентов переиспользуют эти же типы
+const SERDE: &str = "#[derive(serde::Serialize, serde::Deserialize)] #[serde(default)]";
+const TIMESTAMP: &str = "#[serde(with = \"crate::timestamp\")]";
+
 fn main() -> Result<(), Error> {
     let out_dir = PathBuf::from(env::var("OUT_DIR")?);
 
     tonic_prost_build::configure()
+        .message_attribute(".", SERDE)
+        .field_attribute("created_at", TIMESTAMP)
+        .field_attribute("updated_at", TIMESTAMP)
+        .field_attribute("deleted_at", TIMESTAMP)
         .file_descriptor_set_path(out_dir.join("messages_descriptor.bin"))
         .compile_protos(&["src/messages.proto"], &["src"])?;
 
     tonic_prost_build::configure()
+        .message_attribute(".", SERDE)
         .file_descriptor_set_path(out_dir.join("topics_descriptor.bin"))
         .compile_protos(&["src/topics.proto"], &["src"])?;
 
     tonic_prost_build::configure()
+        .file_descriptor_set_path(out_dir.join("users_descriptor.bin"))
+        .compile_protos(&["src/users.proto"], &["src"])?;
+
+    tonic_prost_build::configure()
+        .file_descriptor_set_path(out_dir.join("admin_descriptor.bin"))
+        .compile_protos(&["src/admin.proto"], &["src"])?;
+
+    tonic_prost_build::configure()
+        .message_attribute(".", SERDE)
+        .field_attribute("created_at", TIMESTAMP)
+        .field_attribute("updated_at", TIMESTAMP)
+        .field_attribute("hidden_at", TIMESTAMP)
+        .field_attribute("deleted_at", TIMESTAMP)
         .build_server(false)
         .build_client(false)
         .file_descriptor_set_path(out_dir.join("events_descriptor.bin"))
@@ -1,3 +1,37 @@
+pub mod timestamp {
+    use chrono::{DateTime, Utc};
+    use prost_types::Timestamp;
+    use serde::{Deserialize as _, Deserializer, Serializer, de::Error as _};
+
+    pub fn serialize<S: Serializer>(
+        timestamp: &Option<Timestamp>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match timestamp
+            .as_ref()
+            .and_then(|it| DateTime::<Utc>::from_timestamp(it.seconds, it.nanos.max(0) as u32))
+        {
+            Some(it) => serializer.serialize_some(&it.to_rfc3339()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Timestamp>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|it| {
+                let it = DateTime::parse_from_rfc3339(&it).map_err(D::Error::custom)?;
+
+                Ok(Timestamp {
+                    seconds: it.timestamp(),
+                    nanos: it.timestamp_subsec_nanos() as i32,
+                })
+            })
+            .transpose()
+    }
+}
+
 pub mod messages {
     pub const FILE_DESCRIPTOR_SET: &[u8] =
         tonic::include_file_descriptor_set!("messages_descriptor");
@@ -11,11 +45,38 @@ pub mod topics {
     tonic::include_proto!("bzd.messages.topics");
 }
 
+pub mod users {
+    pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("users_descriptor");
+
+    tonic::include_proto!("bzd.messages.users");
+}
+
+pub mod admin {
+    pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("admin_descriptor");
+
+    tonic::include_proto!("bzd.messages.admin");
+}
+
 pub mod events {
     pub const DESCRIPTOR: &[u8] = tonic::include_file_descriptor_set!("events_descriptor");
 
     tonic::include_proto!("bzd.messages.events");
 
+    pub mod message {
+        use strum_macros::{Display, EnumString};
+
+        #[derive(PartialEq, Debug, EnumString, Display, Clone)]
+        #[strum(ascii_case_insensitive)]
+        pub enum Type {
+            #[strum(serialize = "app.bezdna.message.hidden")]
+            Hidden,
+            #[strum(serialize = "app.bezdna.message.unhidden")]
+            Unhidden,
+            #[strum(serialize = "app.bezdna.message.deleted")]
+            Deleted,
+        }
+    }
+
     pub mod message_topic {
         use strum_macros::{Display, EnumString};
 
@@ -37,8 +98,46 @@ pub mod events {
         pub enum Type {
             #[strum(serialize = "app.bezdna.topic-user.created")]
             Created,
+            #[strum(serialize = "app.bezdna.topic-user.updated")]
+            Updated,
             #[strum(serialize = "app.bezdna.topic-user.deleted")]
             Deleted,
         }
     }
+
+    pub mod topic {
+        use strum_macros::{Display, EnumString};
+
+        #[derive(PartialEq, Debug, EnumString, Display, Clone)]
+        #[strum(ascii_case_insensitive)]
+        pub enum Type {
+            #[strum(serialize = "app.bezdna.topic.updated")]
+            Updated,
+            #[strum(serialize = "app.bezdna.topic.deleted")]
+            Deleted,
+        }
+    }
+
+    pub mod stream {
+        use strum_macros::{Display, EnumString};
+
+        #[derive(PartialEq, Debug, EnumString, Display, Clone)]
+        #[strum(ascii_case_insensitive)]
+        pub enum Type {
+            #[strum(serialize = "app.bezdna.stream.updated")]
+            Updated,
+        }
+    }
+
+    // Входящий эвент от сервиса аккаунтов
+    pub mod user {
+        use strum_macros::{Display, EnumString};
+
+        #[derive(PartialEq, Debug, EnumString, Display, Clone)]
+        #[strum(ascii_case_insensitive)]
+        pub enum Type {
+            #[strum(serialize = "app.bezdna.user.deleted")]
+            Deleted,
+        }
+    }
 }
@@ -15,6 +15,7 @@ pub enum TopicsUsers {
     TopicUserId,
     UserId,
     TopicId,
+    Notifications,
 }
 
 #[derive(DeriveIden)]
@@ -24,6 +25,8 @@ pub enum Messages {
     Text,
     UserId,
     Code,
+    HiddenAt,
+    DeletedAt,
 }
 
 #[derive(DeriveIden)]
@@ -59,3 +62,76 @@ pub enum MessagesUsers {
     UserId,
     IsOwned,
 }
+
+#[derive(DeriveIden)]
+pub enum TopicsTrends {
+    Table,
+    TopicId,
+    Score,
+    ActivityAt,
+}
+
+#[derive(DeriveIden)]
+pub enum UserBlocks {
+    Table,
+    UserBlockId,
+    UserId,
+    BlockedUserId,
+}
+
+#[derive(DeriveIden)]
+pub enum TopicsTransfers {
+    Table,
+    TopicTransferId,
+    TopicId,
+    UserId,
+    ToUserId,
+}
+
+#[derive(DeriveIden)]
+pub enum RateLimits {
+    Table,
+    Key,
+    Tat,
+}
+
+#[derive(DeriveIden)]
+pub enum UserSuspensions {
+    Table,
+    UserSuspensionId,
+    UserId,
+    Reason,
+    Until,
+}
+
+#[derive(DeriveIden)]
+pub enum AuditLogs {
+    Table,
+    AuditLogId,
+    ActorId,
+    Action,
+    Entity,
+    EntityIds,
+    Before,
+    After,
+    RequestId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum UserErasures {
+    Table,
+    UserErasureId,
+    UserId,
+    CompletedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum EventsOutbox {
+    Table,
+    EventOutboxId,
+    Subject,
+    Headers,
+    Payload,
+    CreatedAt,
+}
@@ -10,6 +10,17 @@ mod m20250914_122723_create_messages_topics;
 mod m20251027_072804_create_topics_users;
 mod m20260127_142240_create_messages_users;
 mod m20260221_073831_add_code_to_topics;
+mod m20261019_081512_create_topics_trends;
+mod m20261019_093204_create_user_blocks;
+mod m20261019_104815_add_notifications_to_topics_users;
+mod m20261019_113027_create_topics_transfers;
+mod m20261019_124130_create_rate_limits;
+mod m20261019_141502_add_hidden_at_to_messages;
+mod m20261019_141730_create_user_suspensions;
+mod m20261019_152204_create_audit_log;
+mod m20261019_163015_add_deleted_at_to_messages;
+mod m20261019_163240_create_user_erasures;
+mod m20261019_171204_create_events_outbox;
 
 pub struct Migrator;
 
@@ -25,6 +36,17 @@ impl MigratorTrait for Migrator {
             Box::new(m20251027_072804_create_topics_users::Migration),
             Box::new(m20260127_142240_create_messages_users::Migration),
             Box::new(m20260221_073831_add_code_to_topics::Migration),
+            Box::new(m20261019_081512_create_topics_trends::Migration),
+            Box::new(m20261019_093204_create_user_blocks::Migration),
+            Box::new(m20261019_104815_add_notifications_to_topics_users::Migration),
+            Box::new(m20261019_113027_create_topics_transfers::Migration),
+            Box::new(m20261019_124130_create_rate_limits::Migration),
+            Box::new(m20261019_141502_add_hidden_at_to_messages::Migration),
+            Box::new(m20261019_141730_create_user_suspensions::Migration),
+            Box::new(m20261019_152204_create_audit_log::Migration),
+            Box::new(m20261019_163015_add_deleted_at_to_messages::Migration),
+            Box::new(m20261019_163240_create_user_erasures::Migration),
+            Box::new(m20261019_171204_create_events_outbox::Migration),
         ]
     }
 }
@@ -0,0 +1,49 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::TopicsTrends;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto(TopicsTrends::Table)
+                    .col(uuid(TopicsTrends::TopicId).primary_key())
+                    .col(double(TopicsTrends::Score))
+                    .col(timestamp(TopicsTrends::ActivityAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("topics_trends_score_idx")
+                    .table(TopicsTrends::Table)
+                    .col(TopicsTrends::Score)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("topics_trends_activity_at_idx")
+                    .table(TopicsTrends::Table)
+                    .col(TopicsTrends::ActivityAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TopicsTrends::Table).to_owned())
+            .await
+    }
+}
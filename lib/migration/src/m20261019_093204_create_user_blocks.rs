@@ -0,0 +1,51 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::UserBlocks;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto(UserBlocks::Table)
+                    .col(uuid(UserBlocks::UserBlockId).primary_key())
+                    .col(uuid(UserBlocks::UserId))
+                    .col(uuid(UserBlocks::BlockedUserId))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("user_blocks_user_id_blocked_user_id_udx")
+                    .unique()
+                    .table(UserBlocks::Table)
+                    .col(UserBlocks::UserId)
+                    .col(UserBlocks::BlockedUserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("user_blocks_blocked_user_id_idx")
+                    .table(UserBlocks::Table)
+                    .col(UserBlocks::BlockedUserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UserBlocks::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,51 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::TopicsTransfers;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto(TopicsTransfers::Table)
+                    .col(uuid(TopicsTransfers::TopicTransferId).primary_key())
+                    .col(uuid(TopicsTransfers::TopicId))
+                    .col(uuid(TopicsTransfers::UserId))
+                    .col(uuid(TopicsTransfers::ToUserId))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("topics_transfers_topic_id_udx")
+                    .unique()
+                    .table(TopicsTransfers::Table)
+                    .col(TopicsTransfers::TopicId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("topics_transfers_to_user_id_idx")
+                    .table(TopicsTransfers::Table)
+                    .col(TopicsTransfers::ToUserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TopicsTransfers::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,39 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::RateLimits;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RateLimits::Table)
+                    .col(text(RateLimits::Key).primary_key())
+                    .col(timestamp_with_time_zone(RateLimits::Tat))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("rate_limits_tat_idx")
+                    .table(RateLimits::Table)
+                    .col(RateLimits::Tat)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RateLimits::Table).to_owned())
+            .await
+    }
+}
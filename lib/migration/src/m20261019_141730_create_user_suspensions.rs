@@ -0,0 +1,41 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::UserSuspensions;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto(UserSuspensions::Table)
+                    .col(uuid(UserSuspensions::UserSuspensionId).primary_key())
+                    .col(uuid(UserSuspensions::UserId))
+                    .col(text_null(UserSuspensions::Reason))
+                    .col(timestamp_null(UserSuspensions::Until))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("user_suspensions_user_id_udx")
+                    .unique()
+                    .table(UserSuspensions::Table)
+                    .col(UserSuspensions::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UserSuspensions::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,69 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::AuditLogs;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLogs::Table)
+                    .col(uuid(AuditLogs::AuditLogId).primary_key())
+                    .col(uuid_null(AuditLogs::ActorId))
+                    .col(text(AuditLogs::Action))
+                    .col(text(AuditLogs::Entity))
+                    .col(array(AuditLogs::EntityIds, ColumnType::Uuid))
+                    .col(json_binary_null(AuditLogs::Before))
+                    .col(json_binary_null(AuditLogs::After))
+                    .col(text_null(AuditLogs::RequestId))
+                    .col(timestamp(AuditLogs::CreatedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("audit_logs_actor_id_created_at_idx")
+                    .table(AuditLogs::Table)
+                    .col(AuditLogs::ActorId)
+                    .col(AuditLogs::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // поиск по сущности идет через entity_ids @> ARRAY[..]
+        manager
+            .create_index(
+                Index::create()
+                    .name("audit_logs_entity_ids_idx")
+                    .table(AuditLogs::Table)
+                    .col(AuditLogs::EntityIds)
+                    .index_type(IndexType::Custom(Alias::new("GIN").into_iden()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("audit_logs_created_at_idx")
+                    .table(AuditLogs::Table)
+                    .col(AuditLogs::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuditLogs::Table).to_owned())
+            .await
+    }
+}
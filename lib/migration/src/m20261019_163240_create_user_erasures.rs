@@ -0,0 +1,40 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::UserErasures;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto(UserErasures::Table)
+                    .col(uuid(UserErasures::UserErasureId).primary_key())
+                    .col(uuid(UserErasures::UserId))
+                    .col(timestamp_null(UserErasures::CompletedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("user_erasures_user_id_udx")
+                    .unique()
+                    .table(UserErasures::Table)
+                    .col(UserErasures::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UserErasures::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,30 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::entities::EventsOutbox;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EventsOutbox::Table)
+                    .col(uuid(EventsOutbox::EventOutboxId).primary_key())
+                    .col(text(EventsOutbox::Subject))
+                    .col(json_binary(EventsOutbox::Headers))
+                    .col(binary(EventsOutbox::Payload))
+                    .col(timestamp(EventsOutbox::CreatedAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EventsOutbox::Table).to_owned())
+            .await
+    }
+}
@@ -1,53 +1,172 @@
-use axum::Router;
+use std::time::Duration;
+
+use axum::{Router, middleware, routing::get};
 use bzd_lib::error::Error;
-use bzd_lib::settings::{HttpSettings, Settings as _};
+use bzd_lib::settings::Settings as _;
+use sea_orm::Database;
 use tonic::service::Routes;
-use tracing::info;
+use tonic_health::pb::health_server::{Health, HealthServer};
+use tonic_web::GrpcWebLayer;
+use tracing::{info, warn};
 
-use crate::app::settings::AppSettings;
+use crate::app::settings::{AppSettings, CommandSettings};
 use crate::app::state::AppState;
 
+mod admin;
+mod audit;
+mod auth;
+mod cache;
+mod cors;
 mod current_user;
 mod db;
 mod error;
+mod events;
 mod grpc;
+mod health;
+mod limiter;
 mod mess;
 mod messages;
+mod metrics;
 mod settings;
+mod shutdown;
 mod state;
+mod telemetry;
 mod topics;
+mod users;
 
 pub async fn run() -> Result<(), Error> {
     let settings = AppSettings::new()?;
+    let tracer_provider = telemetry::init(&settings.telemetry)?;
+
     let state = AppState::new(settings.clone()).await?;
 
-    http_and_grpc(&state, &settings.http).await?;
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+
+    tokio::try_join!(
+        shutdown::listen(&state.shutdown, &settings.shutdown),
+        http_and_grpc(&state, &settings, health_service),
+        health::checker(&state, health_reporter, &settings.health),
+        topics::trends_consumer(&state, &settings.nats),
+        admin::erasure_worker(&state),
+        admin::erasure_consumer(&state, &settings.nats),
+        cache::invalidator(&state, &settings),
+        limiter::pruner(&state, &settings.limiter),
+    )?;
+
+    state.messages.mess.nats.flush().await?;
+
+    if let Some(tracer_provider) = tracer_provider {
+        tracer_provider.shutdown()?;
+    }
+
+    info!("app: stopped");
+
+    Ok(())
+}
+
+pub async fn migrate(args: &[String]) -> Result<(), Error> {
+    let settings = CommandSettings::new()?;
+    let tracer_provider = telemetry::init(&settings.telemetry)?;
+
+    let command = db::migrate::Command::try_from(args)?;
+    let conn = Database::connect(&settings.db.base.endpoint).await?;
+
+    db::migrate::run(&conn, command).await?;
+
+    if let Some(tracer_provider) = tracer_provider {
+        tracer_provider.shutdown()?;
+    }
 
     Ok(())
 }
 
-async fn http_and_grpc(state: &AppState, settings: &HttpSettings) -> Result<(), Error> {
+pub async fn export(args: &[String]) -> Result<(), Error> {
+    let settings = CommandSettings::new()?;
+    let tracer_provider = telemetry::init(&settings.telemetry)?;
+
+    let command = admin::export::Command::try_from(args)?;
+    let conn = Database::connect(&settings.db.base.endpoint).await?;
+
+    admin::export::run(conn, &settings.admin, command).await?;
+
+    if let Some(tracer_provider) = tracer_provider {
+        tracer_provider.shutdown()?;
+    }
+
+    Ok(())
+}
+
+pub async fn trends(args: &[String]) -> Result<(), Error> {
+    let settings = CommandSettings::new()?;
+    let tracer_provider = telemetry::init(&settings.telemetry)?;
+
+    let command = topics::trends::Command::try_from(args)?;
+    let conn = Database::connect(&settings.db.base.endpoint).await?;
+
+    topics::trends::run(&conn, &settings.topics, command).await?;
+
+    if let Some(tracer_provider) = tracer_provider {
+        tracer_provider.shutdown()?;
+    }
+
+    Ok(())
+}
+
+async fn http_and_grpc(
+    state: &AppState,
+    settings: &AppSettings,
+    health_service: HealthServer<impl Health>,
+) -> Result<(), Error> {
     let reflection_service = tonic_reflection::server::Builder::configure()
         .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
         .register_encoded_file_descriptor_set(bzd_messages_api::messages::FILE_DESCRIPTOR_SET)
         .register_encoded_file_descriptor_set(bzd_messages_api::topics::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(bzd_messages_api::users::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(bzd_messages_api::admin::FILE_DESCRIPTOR_SET)
         .build_v1alpha()?;
 
-    let (_, health_service) = tonic_health::server::health_reporter();
-
-    let router = Router::new().with_state(());
+    let router = Router::new()
+        .route("/metrics", get(metrics::handler))
+        .route("/livez", get(health::livez))
+        .route("/readyz", get(health::readyz))
+        .merge(messages::http::router())
+        .merge(topics::http::router())
+        .with_state(state.clone());
     let routes = Routes::from(router);
     let router = routes
         .add_service(reflection_service)
         .add_service(health_service)
         .add_service(topics::topics_service(state))
         .add_service(messages::messages_service(state))
-        .into_axum_router();
+        .add_service(users::users_service(state))
+        .add_service(admin::admin_service(state))
+        .into_axum_router()
+        // метрики внутри GrpcWebLayer: снаружи у gRPC-Web grpc-status лежит в трейлер-фрейме тела
+        .layer(middleware::from_fn(metrics::grpc))
+        .layer(GrpcWebLayer::new())
+        .layer(middleware::from_fn(telemetry::grpc))
+        .layer(middleware::from_fn(audit::request_id))
+        .layer(cors::layer(&settings.cors)?);
 
-    let listener = tokio::net::TcpListener::bind(&settings.endpoint).await?;
+    let listener = tokio::net::TcpListener::bind(&settings.http.endpoint).await?;
 
     info!("app: started on {}", listener.local_addr()?);
-    axum::serve(listener, router).await?;
+
+    let shutdown = state.shutdown.clone();
+    let serve = axum::serve(listener, router)
+        .with_graceful_shutdown(async move { shutdown.wait().await })
+        .into_future();
+
+    let timeout = Duration::from_secs(settings.shutdown.timeout);
+    let drain = async {
+        state.shutdown.wait().await;
+        tokio::time::sleep(timeout).await;
+    };
+
+    tokio::select! {
+        res = serve => res?,
+        _ = drain => warn!("app: drain timeout exceeded, in-flight requests dropped"),
+    }
 
     Ok(())
 }
@@ -0,0 +1,30 @@
+use bzd_lib::{error::Error, settings::NATSSettings};
+use bzd_messages_api::admin::admin_service_server::AdminServiceServer;
+use tonic::service::interceptor::InterceptedService;
+
+use crate::app::{admin::grpc::GrpcAdminService, auth::AdminInterceptor, state::AppState};
+
+mod erasure;
+pub mod export;
+mod grpc;
+pub mod repo;
+mod service;
+pub mod settings;
+pub mod state;
+
+pub fn admin_service(
+    state: &AppState,
+) -> InterceptedService<AdminServiceServer<GrpcAdminService>, AdminInterceptor> {
+    AdminServiceServer::with_interceptor(
+        GrpcAdminService::new(state.admin.clone()),
+        AdminInterceptor::new(state.auth.clone()),
+    )
+}
+
+pub async fn erasure_worker(state: &AppState) -> Result<(), Error> {
+    erasure::worker(&state.admin, &state.shutdown).await
+}
+
+pub async fn erasure_consumer(state: &AppState, settings: &NATSSettings) -> Result<(), Error> {
+    erasure::consumer(&state.admin, settings, &state.shutdown).await
+}
@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use async_nats::jetstream::{AckKind, Message, consumer::pull};
+use bzd_lib::{error::Error, settings::NATSSettings};
+use futures::StreamExt as _;
+use tracing::{Instrument as _, error, info, info_span};
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+
+use crate::app::{
+    admin::{service, state::AdminState},
+    shutdown::Shutdown,
+    telemetry,
+};
+
+// пауза перед повторной доставкой, чтобы не крутить эвент, пока база недоступна
+const REDELIVERY_DELAY: Duration = Duration::from_secs(5);
+
+pub async fn worker(state: &AdminState, shutdown: &Shutdown) -> Result<(), Error> {
+    let mut ticker = tokio::time::interval(Duration::from_secs(state.settings.erasure.interval));
+
+    info!("erasure: worker started");
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {},
+            _ = shutdown.wait() => break,
+        }
+
+        let AdminState {
+            db,
+            mess,
+            cache,
+            settings,
+            messages_events,
+            topics_events,
+        } = state;
+
+        // ошибка одной итерации не останавливает воркер, незавершенное подберется следующей
+        if let Err(err) = service::erase_user_data(
+            &db.conn,
+            &mess.js,
+            cache,
+            settings,
+            messages_events,
+            topics_events,
+        )
+        .await
+        {
+            error!("erasure: {}", err);
+        }
+    }
+
+    info!("erasure: worker stopped");
+
+    Ok(())
+}
+
+pub async fn consumer(
+    state: &AdminState,
+    settings: &NATSSettings,
+    shutdown: &Shutdown,
+) -> Result<(), Error> {
+    if state.settings.erasure.subjects.is_empty() {
+        return Ok(());
+    }
+
+    let name = format!("{}-erasure", settings.consumer);
+
+    let consumer = state
+        .mess
+        .js
+        .get_stream(&settings.stream)
+        .await?
+        .get_or_create_consumer(
+            &name,
+            pull::Config {
+                durable_name: Some(name.clone()),
+                filter_subjects: state.settings.erasure.subjects.clone(),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+    let mut messages = consumer.messages().await?;
+
+    info!("erasure: consumer {} started", name);
+
+    loop {
+        let message = tokio::select! {
+            message = messages.next() => message,
+            _ = shutdown.wait() => break,
+        };
+
+        let Some(message) = message else {
+            break;
+        };
+
+        // ошибки стрима и аков логируем и идем дальше, консьюмер не должен ронять сервер
+        let message = match message {
+            Ok(message) => message,
+            Err(err) => {
+                error!("erasure: {}", err);
+                continue;
+            }
+        };
+
+        let span = info_span!(
+            "erasure",
+            otel.name = message.subject.as_str(),
+            otel.kind = "consumer",
+        );
+        let _ = span.set_parent(telemetry::extract(message.headers.as_ref()));
+
+        let ack = handler(state, &message).instrument(span).await;
+
+        if let Err(err) = message.ack_with(ack).await {
+            error!("erasure: {}", err);
+        }
+    }
+
+    info!("erasure: consumer {} stopped", name);
+
+    Ok(())
+}
+
+// Эвент только ставит удаление в очередь, саму работу делает воркер. Акаем только после
+// записи в user_erasures: потерянный эвент значит, что пользователь так и не будет удален
+async fn handler(AdminState { db, .. }: &AdminState, message: &Message) -> AckKind {
+    let req = match delete_user_data::request(message) {
+        Ok(Some(req)) => req,
+        Ok(None) => return AckKind::Ack,
+        Err(err) => {
+            error!("erasure: {}", err);
+            return AckKind::Term;
+        }
+    };
+
+    if let Err(err) = service::delete_user_data(&db.conn, req).await {
+        error!("erasure: {}", err);
+        return AckKind::Nak(Some(REDELIVERY_DELAY));
+    }
+
+    AckKind::Ack
+}
+
+mod delete_user_data {
+    use async_nats::jetstream::Message;
+    use bzd_messages_api::events::{User, user};
+
+    use crate::app::{admin::service::delete_user_data::Request, error::AppError, events};
+
+    pub fn request(message: &Message) -> Result<Option<Request>, AppError> {
+        let Some(received) = events::receive(message.headers.as_ref(), &message.payload)? else {
+            return Ok(None);
+        };
+
+        let Ok(user::Type::Deleted) = received.tp.parse() else {
+            return Ok(None);
+        };
+
+        let payload: User = received.decode()?;
+
+        Ok(Some(Request {
+            current_user: None,
+            user_id: payload.user_id().parse()?,
+        }))
+    }
+}
@@ -0,0 +1,91 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use bzd_lib::error::Error;
+use futures::StreamExt as _;
+use sea_orm::DbConn;
+use tokio::{fs::File, io::AsyncWriteExt as _};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::app::{
+    admin::{
+        service::{
+            self,
+            export_user_data::{Chunk, Request},
+        },
+        settings::AdminSettings,
+    },
+    error::AppError,
+};
+
+pub struct Command {
+    pub user_id: Uuid,
+    pub dir: PathBuf,
+}
+
+// export <user_id> [dir], по умолчанию пишет в ./export-<user_id>
+impl TryFrom<&[String]> for Command {
+    type Error = AppError;
+
+    fn try_from(args: &[String]) -> Result<Self, Self::Error> {
+        let user_id: Uuid = args.first().ok_or(AppError::Validation)?.parse()?;
+
+        let dir = args
+            .get(1)
+            .map_or_else(|| PathBuf::from(format!("export-{user_id}")), PathBuf::from);
+
+        Ok(Self { user_id, dir })
+    }
+}
+
+// Секция пишется в свой <section>.ndjson, манифест последним в manifest.json
+pub async fn run(db: DbConn, settings: &AdminSettings, command: Command) -> Result<(), Error> {
+    tokio::fs::create_dir_all(&command.dir).await?;
+
+    let mut chunks = service::export_user_data(
+        Arc::new(db),
+        settings.clone(),
+        Request {
+            user_id: command.user_id,
+        },
+    );
+
+    let mut files = HashMap::new();
+
+    while let Some(chunk) = chunks.next().await {
+        match chunk? {
+            Chunk::Data { section, data } => {
+                let file = match files.entry(section) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let path = command.dir.join(format!("{}.ndjson", entry.key().name()));
+                        entry.insert(File::create(path).await?)
+                    }
+                };
+
+                file.write_all(&data).await?;
+            }
+            Chunk::Manifest(manifest) => {
+                for file in files.values_mut() {
+                    file.flush().await?;
+                }
+
+                let path = command.dir.join("manifest.json");
+                let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(AppError::from)?;
+                tokio::fs::write(path, manifest_json).await?;
+
+                for section in &manifest.sections {
+                    info!("export: {} {}", section.section.name(), section.count);
+                }
+            }
+        }
+    }
+
+    info!("export: done {}", command.dir.display());
+
+    Ok(())
+}
@@ -0,0 +1,626 @@
+use std::pin::Pin;
+
+use bzd_messages_api::admin::{
+    DeleteTopicRequest, DeleteTopicResponse, DeleteTopicUserRequest, DeleteTopicUserResponse,
+    DeleteUserDataRequest, DeleteUserDataResponse, ExportUserDataRequest, ExportUserDataResponse,
+    GetAuditLogRequest, GetAuditLogResponse, GetUserContentRequest, GetUserContentResponse,
+    HideMessageRequest, HideMessageResponse, SuspendUserRequest, SuspendUserResponse,
+    UnhideMessageRequest, UnhideMessageResponse, UnsuspendUserRequest, UnsuspendUserResponse,
+    admin_service_server::AdminService,
+};
+use futures::Stream;
+use tonic::{Request, Response, Status};
+
+use crate::app::{admin::state::AdminState, auth};
+
+pub struct GrpcAdminService {
+    pub state: AdminState,
+}
+
+impl GrpcAdminService {
+    pub fn new(state: AdminState) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl AdminService for GrpcAdminService {
+    type ExportUserDataStream =
+        Pin<Box<dyn Stream<Item = Result<ExportUserDataResponse, Status>> + Send>>;
+
+    async fn hide_message(
+        &self,
+        req: Request<HideMessageRequest>,
+    ) -> Result<Response<HideMessageResponse>, Status> {
+        hide_message::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(HideMessageResponse::default()))
+    }
+
+    async fn unhide_message(
+        &self,
+        req: Request<UnhideMessageRequest>,
+    ) -> Result<Response<UnhideMessageResponse>, Status> {
+        unhide_message::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(UnhideMessageResponse::default()))
+    }
+
+    async fn suspend_user(
+        &self,
+        req: Request<SuspendUserRequest>,
+    ) -> Result<Response<SuspendUserResponse>, Status> {
+        let res = suspend_user::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn unsuspend_user(
+        &self,
+        req: Request<UnsuspendUserRequest>,
+    ) -> Result<Response<UnsuspendUserResponse>, Status> {
+        unsuspend_user::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(UnsuspendUserResponse::default()))
+    }
+
+    async fn delete_topic(
+        &self,
+        req: Request<DeleteTopicRequest>,
+    ) -> Result<Response<DeleteTopicResponse>, Status> {
+        delete_topic::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(DeleteTopicResponse::default()))
+    }
+
+    async fn delete_topic_user(
+        &self,
+        req: Request<DeleteTopicUserRequest>,
+    ) -> Result<Response<DeleteTopicUserResponse>, Status> {
+        delete_topic_user::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(DeleteTopicUserResponse::default()))
+    }
+
+    async fn get_user_content(
+        &self,
+        req: Request<GetUserContentRequest>,
+    ) -> Result<Response<GetUserContentResponse>, Status> {
+        let res = get_user_content::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn get_audit_log(
+        &self,
+        req: Request<GetAuditLogRequest>,
+    ) -> Result<Response<GetAuditLogResponse>, Status> {
+        let res = get_audit_log::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn export_user_data(
+        &self,
+        req: Request<ExportUserDataRequest>,
+    ) -> Result<Response<Self::ExportUserDataStream>, Status> {
+        let res = export_user_data::handler(&self.state, auth::into_inner(req))?;
+
+        Ok(Response::new(Box::pin(res)))
+    }
+
+    async fn delete_user_data(
+        &self,
+        req: Request<DeleteUserDataRequest>,
+    ) -> Result<Response<DeleteUserDataResponse>, Status> {
+        let res = delete_user_data::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(res))
+    }
+}
+
+mod hide_message {
+    use bzd_messages_api::admin::HideMessageRequest;
+
+    use crate::app::{
+        admin::{
+            service::{self, hide_message::Request},
+            state::AdminState,
+        },
+        current_user::CurrentUser,
+        error::AppError,
+    };
+
+    pub async fn handler(
+        AdminState {
+            db,
+            mess,
+            cache,
+            messages_events,
+            ..
+        }: &AdminState,
+        req: HideMessageRequest,
+    ) -> Result<(), AppError> {
+        service::hide_message(&db.conn, &mess.js, cache, messages_events, req.try_into()?).await
+    }
+
+    impl TryFrom<HideMessageRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: HideMessageRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                message_id: req.message_id().parse()?,
+                hidden: true,
+            })
+        }
+    }
+}
+
+mod unhide_message {
+    use bzd_messages_api::admin::UnhideMessageRequest;
+
+    use crate::app::{
+        admin::{
+            service::{self, hide_message::Request},
+            state::AdminState,
+        },
+        current_user::CurrentUser,
+        error::AppError,
+    };
+
+    pub async fn handler(
+        AdminState {
+            db,
+            mess,
+            cache,
+            messages_events,
+            ..
+        }: &AdminState,
+        req: UnhideMessageRequest,
+    ) -> Result<(), AppError> {
+        service::hide_message(&db.conn, &mess.js, cache, messages_events, req.try_into()?).await
+    }
+
+    impl TryFrom<UnhideMessageRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: UnhideMessageRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                message_id: req.message_id().parse()?,
+                hidden: false,
+            })
+        }
+    }
+}
+
+mod suspend_user {
+    use bzd_messages_api::admin::{SuspendUserRequest, SuspendUserResponse};
+
+    use crate::app::{
+        admin::{
+            service::{
+                self,
+                suspend_user::{Request, Response},
+            },
+            state::AdminState,
+        },
+        current_user::CurrentUser,
+        error::AppError,
+        grpc,
+    };
+
+    pub async fn handler(
+        AdminState { db, .. }: &AdminState,
+        req: SuspendUserRequest,
+    ) -> Result<SuspendUserResponse, AppError> {
+        let res = service::suspend_user(&db.conn, req.try_into()?).await?;
+
+        Ok(res.into())
+    }
+
+    impl TryFrom<SuspendUserRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: SuspendUserRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                user_id: req.user_id().parse()?,
+                reason: req.reason,
+                until: req.until.map(grpc::from_proto_timestamp).transpose()?,
+            })
+        }
+    }
+
+    impl From<Response> for SuspendUserResponse {
+        fn from(res: Response) -> Self {
+            Self {
+                user_suspension_id: Some(res.user_suspension.user_suspension_id.into()),
+            }
+        }
+    }
+}
+
+mod unsuspend_user {
+    use bzd_messages_api::admin::UnsuspendUserRequest;
+
+    use crate::app::{
+        admin::{
+            service::{self, unsuspend_user::Request},
+            state::AdminState,
+        },
+        current_user::CurrentUser,
+        error::AppError,
+    };
+
+    pub async fn handler(
+        AdminState { db, .. }: &AdminState,
+        req: UnsuspendUserRequest,
+    ) -> Result<(), AppError> {
+        service::unsuspend_user(&db.conn, req.try_into()?).await
+    }
+
+    impl TryFrom<UnsuspendUserRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: UnsuspendUserRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                user_id: req.user_id().parse()?,
+            })
+        }
+    }
+}
+
+mod delete_topic {
+    use bzd_messages_api::admin::DeleteTopicRequest;
+
+    use crate::app::{
+        admin::{
+            service::{self, delete_topic::Request},
+            state::AdminState,
+        },
+        current_user::CurrentUser,
+        error::AppError,
+    };
+
+    pub async fn handler(
+        AdminState {
+            db,
+            mess,
+            cache,
+            messages_events,
+            topics_events,
+            ..
+        }: &AdminState,
+        req: DeleteTopicRequest,
+    ) -> Result<(), AppError> {
+        service::delete_topic(
+            &db.conn,
+            &mess.js,
+            cache,
+            messages_events,
+            topics_events,
+            req.try_into()?,
+        )
+        .await
+    }
+
+    impl TryFrom<DeleteTopicRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: DeleteTopicRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                topic_id: req.topic_id().parse()?,
+            })
+        }
+    }
+}
+
+mod delete_topic_user {
+    use bzd_messages_api::admin::DeleteTopicUserRequest;
+
+    use crate::app::{
+        admin::{
+            service::{self, delete_topic_user::Request},
+            state::AdminState,
+        },
+        current_user::CurrentUser,
+        error::AppError,
+    };
+
+    pub async fn handler(
+        AdminState {
+            db,
+            mess,
+            topics_events,
+            ..
+        }: &AdminState,
+        req: DeleteTopicUserRequest,
+    ) -> Result<(), AppError> {
+        service::delete_topic_user(&db.conn, &mess.js, topics_events, req.try_into()?).await
+    }
+
+    impl TryFrom<DeleteTopicUserRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: DeleteTopicUserRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                topic_user_id: req.topic_user_id().parse()?,
+            })
+        }
+    }
+}
+
+mod get_user_content {
+    use bzd_messages_api::admin::{
+        GetUserContentRequest, GetUserContentResponse, get_user_content_response::Suspension,
+    };
+
+    use crate::app::{
+        admin::{
+            repo::UserSuspensionModel,
+            service::{
+                self,
+                get_user_content::{Request, Response},
+            },
+            state::AdminState,
+        },
+        error::AppError,
+        grpc::ToProtoTimestamp as _,
+    };
+
+    pub async fn handler(
+        AdminState { db, settings, .. }: &AdminState,
+        req: GetUserContentRequest,
+    ) -> Result<GetUserContentResponse, AppError> {
+        let res = service::get_user_content(db.read_conn(), settings, req.try_into()?).await?;
+
+        Ok(res.into())
+    }
+
+    impl TryFrom<GetUserContentRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: GetUserContentRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                user_id: req.user_id().parse()?,
+                page_token: req.page_token.as_deref().map(str::parse).transpose()?,
+                page_size: req.page_size,
+            })
+        }
+    }
+
+    impl From<Response> for GetUserContentResponse {
+        fn from(res: Response) -> Self {
+            Self {
+                message_ids: res
+                    .messages
+                    .items
+                    .iter()
+                    .map(|it| it.message_id.into())
+                    .collect(),
+                older_page_token: res.messages.older.map(|it| it.to_string()),
+                hidden_message_ids: res
+                    .messages
+                    .items
+                    .iter()
+                    .filter(|it| it.hidden_at.is_some())
+                    .map(|it| it.message_id.into())
+                    .collect(),
+                topic_ids: res.topics.iter().map(|it| it.topic_id.into()).collect(),
+                topic_user_ids: res
+                    .topics_users
+                    .iter()
+                    .map(|it| it.topic_user_id.into())
+                    .collect(),
+                suspension: res
+                    .user_suspension
+                    .filter(|it| it.is_active())
+                    .map(|it| (&it).into()),
+            }
+        }
+    }
+
+    impl From<&UserSuspensionModel> for Suspension {
+        fn from(user_suspension: &UserSuspensionModel) -> Self {
+            Self {
+                user_suspension_id: Some(user_suspension.user_suspension_id.into()),
+                reason: user_suspension.reason.clone(),
+                until: user_suspension.until.and_then(|it| it.to_option_proto()),
+                created_at: user_suspension.created_at.to_option_proto(),
+            }
+        }
+    }
+}
+
+mod get_audit_log {
+    use bzd_messages_api::admin::{
+        GetAuditLogRequest, GetAuditLogResponse, get_audit_log_response::Entry,
+    };
+    use sea_orm::ActiveEnum as _;
+
+    use crate::app::{
+        admin::{
+            service::{
+                self,
+                get_audit_log::{Request, Response},
+            },
+            state::AdminState,
+        },
+        audit::repo::AuditLogModel,
+        error::AppError,
+        grpc::{self, ToProtoTimestamp as _},
+    };
+
+    pub async fn handler(
+        AdminState { db, settings, .. }: &AdminState,
+        req: GetAuditLogRequest,
+    ) -> Result<GetAuditLogResponse, AppError> {
+        let res = service::get_audit_log(db.read_conn(), settings, req.try_into()?).await?;
+
+        Ok(res.into())
+    }
+
+    impl TryFrom<GetAuditLogRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: GetAuditLogRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                actor_id: req.actor_id.as_deref().map(str::parse).transpose()?,
+                entity_id: req.entity_id.as_deref().map(str::parse).transpose()?,
+                from: req.from.map(grpc::from_proto_timestamp).transpose()?,
+                to: req.to.map(grpc::from_proto_timestamp).transpose()?,
+                page_token: req.page_token.as_deref().map(str::parse).transpose()?,
+                page_size: req.page_size,
+            })
+        }
+    }
+
+    impl From<Response> for GetAuditLogResponse {
+        fn from(res: Response) -> Self {
+            Self {
+                entries: res.audit_log.items.iter().map(Into::into).collect(),
+                older_page_token: res.audit_log.older.map(|it| it.to_string()),
+            }
+        }
+    }
+
+    impl From<&AuditLogModel> for Entry {
+        fn from(audit_log: &AuditLogModel) -> Self {
+            Self {
+                audit_log_id: Some(audit_log.audit_log_id.into()),
+                actor_id: audit_log.actor_id.map(Into::into),
+                action: Some(audit_log.action.to_value()),
+                entity: Some(audit_log.entity.to_value()),
+                entity_ids: audit_log
+                    .entity_ids
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+                before: audit_log.before.as_ref().map(ToString::to_string),
+                after: audit_log.after.as_ref().map(ToString::to_string),
+                request_id: audit_log.request_id.clone(),
+                created_at: audit_log.created_at.to_option_proto(),
+            }
+        }
+    }
+}
+
+mod export_user_data {
+    use bzd_messages_api::admin::{
+        ExportUserDataRequest, ExportUserDataResponse,
+        export_user_data_response::{Manifest, manifest::Section},
+    };
+    use futures::{Stream, StreamExt as _};
+    use tonic::Status;
+
+    use crate::app::{
+        admin::{
+            service::{
+                self,
+                export_user_data::{Chunk, ManifestSection, Request},
+            },
+            state::AdminState,
+        },
+        error::AppError,
+        grpc::ToProtoTimestamp as _,
+    };
+
+    pub fn handler(
+        AdminState { db, settings, .. }: &AdminState,
+        req: ExportUserDataRequest,
+    ) -> Result<impl Stream<Item = Result<ExportUserDataResponse, Status>> + use<>, AppError> {
+        let res =
+            service::export_user_data(db.read_conn().clone(), settings.clone(), req.try_into()?);
+
+        Ok(res.map(|it| it.map(ExportUserDataResponse::from).map_err(Status::from)))
+    }
+
+    impl TryFrom<ExportUserDataRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: ExportUserDataRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                user_id: req.user_id().parse()?,
+            })
+        }
+    }
+
+    impl From<Chunk> for ExportUserDataResponse {
+        fn from(chunk: Chunk) -> Self {
+            match chunk {
+                Chunk::Data { section, data } => Self {
+                    section: Some(section.name().into()),
+                    data: Some(data.into()),
+                    manifest: None,
+                },
+                Chunk::Manifest(manifest) => Self {
+                    section: None,
+                    data: None,
+                    manifest: Some(Manifest {
+                        user_id: Some(manifest.user_id.into()),
+                        sections: manifest.sections.iter().map(Into::into).collect(),
+                        created_at: manifest.created_at.to_option_proto(),
+                    }),
+                },
+            }
+        }
+    }
+
+    impl From<&ManifestSection> for Section {
+        fn from(section: &ManifestSection) -> Self {
+            Self {
+                name: Some(section.section.name().into()),
+                count: Some(section.count),
+            }
+        }
+    }
+}
+
+mod delete_user_data {
+    use bzd_messages_api::admin::{DeleteUserDataRequest, DeleteUserDataResponse};
+
+    use crate::app::{
+        admin::{
+            service::{
+                self,
+                delete_user_data::{Request, Response},
+            },
+            state::AdminState,
+        },
+        current_user::CurrentUser,
+        error::AppError,
+    };
+
+    pub async fn handler(
+        AdminState { db, .. }: &AdminState,
+        req: DeleteUserDataRequest,
+    ) -> Result<DeleteUserDataResponse, AppError> {
+        let res = service::delete_user_data(&db.conn, req.try_into()?).await?;
+
+        Ok(res.into())
+    }
+
+    impl TryFrom<DeleteUserDataRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: DeleteUserDataRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                user_id: req.user_id().parse()?,
+            })
+        }
+    }
+
+    impl From<Response> for DeleteUserDataResponse {
+        fn from(res: Response) -> Self {
+            Self {
+                user_erasure_id: Some(res.user_erasure.user_erasure_id.into()),
+            }
+        }
+    }
+}
@@ -0,0 +1,446 @@
+use chrono::{NaiveDateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait as _,
+    ActiveValue::Set,
+    ColumnTrait, Condition, ConnectionTrait, EntityTrait, IntoActiveModel as _, QueryFilter as _,
+    QueryOrder as _, QuerySelect as _, Select,
+    prelude::Expr,
+    sea_query::{OnConflict, Query},
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app::{
+    error::AppError,
+    messages::repo::{
+        MessageModel, MessageStreamModel, MessageTopicModel, StreamModel, message, message_stream,
+        message_topic, message_user, stream, user_block, user_suspension,
+    },
+    topics::repo::{TopicUserModel, topic, topic_transfer, topic_trend, topic_user},
+};
+
+pub mod user_erasure;
+
+pub type UserSuspensionModel = user_suspension::Model;
+pub type UserErasureModel = user_erasure::Model;
+
+// Чужие строки, удаленные вместе с топиком: консьюмеры должны узнать и о них
+pub struct TopicCascade {
+    pub messages_topics: Vec<MessageTopicModel>,
+    pub topics_users: Vec<TopicUserModel>,
+}
+
+#[instrument(skip_all)]
+pub async fn update_message_hidden_at<T: ConnectionTrait>(
+    db: &T,
+    message: MessageModel,
+    hidden_at: Option<NaiveDateTime>,
+) -> Result<MessageModel, AppError> {
+    let mut message = message.into_active_model();
+    message.hidden_at = Set(hidden_at);
+    message.updated_at = Set(Utc::now().naive_utc());
+
+    let message = message.update(db).await?;
+
+    Ok(message)
+}
+
+#[instrument(skip_all)]
+pub async fn update_stream_text<T: ConnectionTrait>(
+    db: &T,
+    stream: StreamModel,
+    text: String,
+) -> Result<StreamModel, AppError> {
+    let mut stream = stream.into_active_model();
+    stream.text = Set(text);
+    stream.updated_at = Set(Utc::now().naive_utc());
+
+    let stream = stream.update(db).await?;
+
+    Ok(stream)
+}
+
+#[instrument(skip_all)]
+pub async fn upsert_user_suspension<T: ConnectionTrait>(
+    db: &T,
+    model: UserSuspensionModel,
+) -> Result<UserSuspensionModel, AppError> {
+    user_suspension::Entity::insert(model.clone().into_active_model())
+        .on_conflict(
+            OnConflict::column(user_suspension::Column::UserId)
+                .update_columns([
+                    user_suspension::Column::Reason,
+                    user_suspension::Column::Until,
+                    user_suspension::Column::UpdatedAt,
+                ])
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+
+    let user_suspension = user_suspension::Entity::find()
+        .filter(user_suspension::Column::UserId.eq(model.user_id))
+        .one(db)
+        .await?
+        .ok_or(AppError::Unreachable)?;
+
+    Ok(user_suspension)
+}
+
+#[instrument(skip_all)]
+pub async fn delete_user_suspension<T: ConnectionTrait>(
+    db: &T,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    user_suspension::Entity::delete_many()
+        .filter(user_suspension::Column::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+// Внешних ключей в схеме нет, поэтому все ссылки на топик чистим сами
+#[instrument(skip_all)]
+pub async fn delete_topic<T: ConnectionTrait>(
+    db: &T,
+    topic_id: Uuid,
+) -> Result<TopicCascade, AppError> {
+    let messages_topics = message_topic::Entity::find()
+        .filter(message_topic::Column::TopicId.eq(topic_id))
+        .all(db)
+        .await?;
+
+    let topics_users = topic_user::Entity::find()
+        .filter(topic_user::Column::TopicId.eq(topic_id))
+        .all(db)
+        .await?;
+
+    message_topic::Entity::delete_many()
+        .filter(message_topic::Column::TopicId.eq(topic_id))
+        .exec(db)
+        .await?;
+
+    topic_user::Entity::delete_many()
+        .filter(topic_user::Column::TopicId.eq(topic_id))
+        .exec(db)
+        .await?;
+
+    topic_trend::Entity::delete_many()
+        .filter(topic_trend::Column::TopicId.eq(topic_id))
+        .exec(db)
+        .await?;
+
+    topic_transfer::Entity::delete_many()
+        .filter(topic_transfer::Column::TopicId.eq(topic_id))
+        .exec(db)
+        .await?;
+
+    topic::Entity::delete_by_id(topic_id).exec(db).await?;
+
+    Ok(TopicCascade {
+        messages_topics,
+        topics_users,
+    })
+}
+
+pub fn user_messages(user_id: Uuid) -> Select<message::Entity> {
+    message::Entity::find().filter(message::Column::UserId.eq(user_id))
+}
+
+pub fn user_messages_users(user_id: Uuid) -> Select<message_user::Entity> {
+    message_user::Entity::find().filter(message_user::Column::UserId.eq(user_id))
+}
+
+// Стримы, в которых пользователь участвовал, а не только те, что начинались с его сообщений
+pub fn user_streams(user_id: Uuid) -> Select<stream::Entity> {
+    stream::Entity::find().filter(
+        stream::Column::MessageId.in_subquery(
+            Query::select()
+                .column(message_user::Column::MessageId)
+                .from(message_user::Entity)
+                .and_where(message_user::Column::UserId.eq(user_id))
+                .to_owned(),
+        ),
+    )
+}
+
+pub fn user_topics(user_id: Uuid) -> Select<topic::Entity> {
+    topic::Entity::find().filter(topic::Column::UserId.eq(user_id))
+}
+
+pub fn user_topics_users(user_id: Uuid) -> Select<topic_user::Entity> {
+    topic_user::Entity::find().filter(topic_user::Column::UserId.eq(user_id))
+}
+
+// Привязки его сообщений к чужим топикам и чужих сообщений к его топикам
+pub fn user_messages_topics(user_id: Uuid) -> Select<message_topic::Entity> {
+    message_topic::Entity::find().filter(
+        Condition::any()
+            .add(
+                message_topic::Column::MessageId.in_subquery(
+                    Query::select()
+                        .column(message::Column::MessageId)
+                        .from(message::Entity)
+                        .and_where(message::Column::UserId.eq(user_id))
+                        .to_owned(),
+                ),
+            )
+            .add(
+                message_topic::Column::TopicId.in_subquery(
+                    Query::select()
+                        .column(topic::Column::TopicId)
+                        .from(topic::Entity)
+                        .and_where(topic::Column::UserId.eq(user_id))
+                        .to_owned(),
+                ),
+            ),
+    )
+}
+
+// Пачки идут по возрастанию первичного ключа, after это последний ключ предыдущей пачки,
+// удаление передает None, потому что обработанные строки из выборки уже выпадают
+#[instrument(skip_all)]
+pub async fn get_batch<T: ConnectionTrait, E: EntityTrait>(
+    db: &T,
+    query: Select<E>,
+    column: impl ColumnTrait,
+    after: Option<Uuid>,
+    limit: u64,
+) -> Result<Vec<E::Model>, AppError> {
+    let query = match after {
+        Some(after) => query.filter(column.gt(after)),
+        None => query,
+    };
+
+    let batch = query.order_by_asc(column).limit(limit).all(db).await?;
+
+    Ok(batch)
+}
+
+// Повторный запрос на удаление снова ставит его в очередь, даже если раньше оно завершилось
+#[instrument(skip_all)]
+pub async fn upsert_user_erasure<T: ConnectionTrait>(
+    db: &T,
+    model: UserErasureModel,
+) -> Result<UserErasureModel, AppError> {
+    user_erasure::Entity::insert(model.clone().into_active_model())
+        .on_conflict(
+            OnConflict::column(user_erasure::Column::UserId)
+                .update_columns([
+                    user_erasure::Column::CompletedAt,
+                    user_erasure::Column::UpdatedAt,
+                ])
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+
+    let user_erasure = user_erasure::Entity::find()
+        .filter(user_erasure::Column::UserId.eq(model.user_id))
+        .one(db)
+        .await?
+        .ok_or(AppError::Unreachable)?;
+
+    Ok(user_erasure)
+}
+
+#[instrument(skip_all)]
+pub async fn get_pending_user_erasures<T: ConnectionTrait>(
+    db: &T,
+    limit: u64,
+) -> Result<Vec<UserErasureModel>, AppError> {
+    let user_erasures = user_erasure::Entity::find()
+        .filter(user_erasure::Column::CompletedAt.is_null())
+        .order_by_asc(user_erasure::Column::UpdatedAt)
+        .limit(limit)
+        .all(db)
+        .await?;
+
+    Ok(user_erasures)
+}
+
+// Каждая пачка берет строку под FOR UPDATE, так реплики обрабатывают одного пользователя по очереди,
+// а следующая пачка уже не видит того, что успела сделать предыдущая
+#[instrument(skip_all)]
+pub async fn lock_user_erasure<T: ConnectionTrait>(
+    db: &T,
+    user_erasure_id: Uuid,
+) -> Result<Option<UserErasureModel>, AppError> {
+    let user_erasure = user_erasure::Entity::find_by_id(user_erasure_id)
+        .filter(user_erasure::Column::CompletedAt.is_null())
+        .lock_exclusive()
+        .one(db)
+        .await?;
+
+    Ok(user_erasure)
+}
+
+#[instrument(skip_all)]
+pub async fn complete_user_erasure<T: ConnectionTrait>(
+    db: &T,
+    user_erasure: UserErasureModel,
+) -> Result<UserErasureModel, AppError> {
+    let now = Utc::now().naive_utc();
+
+    let mut user_erasure = user_erasure.into_active_model();
+    user_erasure.completed_at = Set(Some(now));
+    user_erasure.updated_at = Set(now);
+
+    let user_erasure = user_erasure.update(db).await?;
+
+    Ok(user_erasure)
+}
+
+// Надгробие: автор и текст стираются, code уникален, поэтому в него идет сам message_id
+#[instrument(skip_all)]
+pub async fn tombstone_message<T: ConnectionTrait>(
+    db: &T,
+    message: MessageModel,
+) -> Result<MessageModel, AppError> {
+    let now = Utc::now().naive_utc();
+    let code = message.message_id.to_string();
+
+    let mut message = message.into_active_model();
+    message.user_id = Set(Uuid::nil());
+    message.text = Set(String::new());
+    message.code = Set(code);
+    message.deleted_at = Set(Some(now));
+    message.updated_at = Set(now);
+
+    let message = message.update(db).await?;
+
+    Ok(message)
+}
+
+#[instrument(skip_all)]
+pub async fn get_messages_topics_by_message_ids<T: ConnectionTrait>(
+    db: &T,
+    message_ids: Vec<Uuid>,
+) -> Result<Vec<MessageTopicModel>, AppError> {
+    let messages_topics = message_topic::Entity::find()
+        .filter(message_topic::Column::MessageId.is_in(message_ids))
+        .all(db)
+        .await?;
+
+    Ok(messages_topics)
+}
+
+#[instrument(skip_all)]
+pub async fn delete_messages_topics_by_message_ids<T: ConnectionTrait>(
+    db: &T,
+    message_ids: Vec<Uuid>,
+) -> Result<(), AppError> {
+    message_topic::Entity::delete_many()
+        .filter(message_topic::Column::MessageId.is_in(message_ids))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+#[instrument(skip_all)]
+pub async fn get_messages_streams_by_message_ids<T: ConnectionTrait>(
+    db: &T,
+    message_ids: Vec<Uuid>,
+) -> Result<Vec<MessageStreamModel>, AppError> {
+    let messages_streams = message_stream::Entity::find()
+        .filter(message_stream::Column::MessageId.is_in(message_ids))
+        .all(db)
+        .await?;
+
+    Ok(messages_streams)
+}
+
+#[instrument(skip_all)]
+pub async fn get_streams_by_ids<T: ConnectionTrait>(
+    db: &T,
+    stream_ids: Vec<Uuid>,
+) -> Result<Vec<StreamModel>, AppError> {
+    let streams = stream::Entity::find()
+        .filter(stream::Column::StreamId.is_in(stream_ids))
+        .all(db)
+        .await?;
+
+    Ok(streams)
+}
+
+// Счетчик меняется выражением, а не присваиванием, чтобы не потерять параллельные ответы в стрим
+#[instrument(skip_all)]
+pub async fn erase_stream<T: ConnectionTrait>(
+    db: &T,
+    stream_id: Uuid,
+    replies: i64,
+    clear_text: bool,
+) -> Result<(), AppError> {
+    let mut query = stream::Entity::update_many()
+        .col_expr(
+            stream::Column::MessagesCount,
+            Expr::cust_with_values("GREATEST(messages_count - $1, 0)", [replies]),
+        )
+        .col_expr(
+            stream::Column::UpdatedAt,
+            Expr::value(Utc::now().naive_utc()),
+        );
+
+    // текст стрима это копия корневого сообщения
+    if clear_text {
+        query = query.col_expr(stream::Column::Text, Expr::value(String::new()));
+    }
+
+    query
+        .filter(stream::Column::StreamId.eq(stream_id))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+#[instrument(skip_all)]
+pub async fn get_topics_users_by_user_id<T: ConnectionTrait>(
+    db: &T,
+    user_id: Uuid,
+) -> Result<Vec<TopicUserModel>, AppError> {
+    let topics_users = user_topics_users(user_id).all(db).await?;
+
+    Ok(topics_users)
+}
+
+// Все, что осталось от пользователя после надгробий и удаления топиков
+#[instrument(skip_all)]
+pub async fn delete_user_rows<T: ConnectionTrait>(db: &T, user_id: Uuid) -> Result<(), AppError> {
+    topic_user::Entity::delete_many()
+        .filter(topic_user::Column::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+
+    message_user::Entity::delete_many()
+        .filter(message_user::Column::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+
+    topic_transfer::Entity::delete_many()
+        .filter(
+            Condition::any()
+                .add(topic_transfer::Column::UserId.eq(user_id))
+                .add(topic_transfer::Column::ToUserId.eq(user_id)),
+        )
+        .exec(db)
+        .await?;
+
+    user_block::Entity::delete_many()
+        .filter(
+            Condition::any()
+                .add(user_block::Column::UserId.eq(user_id))
+                .add(user_block::Column::BlockedUserId.eq(user_id)),
+        )
+        .exec(db)
+        .await?;
+
+    user_suspension::Entity::delete_many()
+        .filter(user_suspension::Column::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use chrono::Utc;
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "user_erasures")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_erasure_id: Uuid,
+    pub user_id: Uuid,
+    pub completed_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl Model {
+    pub fn new(user_id: Uuid) -> Self {
+        let now = Utc::now().naive_utc();
+        let user_erasure_id = Uuid::now_v7();
+
+        Self {
+            user_erasure_id,
+            user_id,
+            completed_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,1237 @@
+use std::sync::Arc;
+
+use async_nats::jetstream::Context;
+use bzd_messages_api::events::{
+    message::Type as MessageType, message_topic::Type as MessageTopicType,
+    stream::Type as StreamType, topic::Type as TopicType, topic_user::Type as TopicUserType,
+};
+use chrono::{NaiveDateTime, Utc};
+use futures::{
+    SinkExt as _,
+    channel::mpsc::{self, Receiver, Sender},
+};
+use sea_orm::{
+    AccessMode, DatabaseTransaction, DbConn, EntityTrait, IsolationLevel, Select,
+    TransactionTrait as _,
+};
+use serde::Serialize;
+use tracing::{Instrument as _, instrument};
+use uuid::Uuid;
+
+use crate::app::{
+    admin::{
+        repo::{self, UserErasureModel, UserSuspensionModel},
+        settings::AdminSettings,
+    },
+    audit::{self, repo::AuditLogAction},
+    cache::CacheState,
+    error::AppError,
+    events,
+    messages::{
+        self,
+        page::{self, Page},
+        repo::{message, message_topic, message_user, stream},
+        settings::EventsSettings as MessagesEventsSettings,
+    },
+    metrics,
+    topics::{
+        self,
+        repo::{topic, topic_user},
+        settings::EventsSettings as TopicsEventsSettings,
+    },
+};
+
+#[instrument(skip_all)]
+pub async fn hide_message(
+    db: &DbConn,
+    js: &Context,
+    cache: &CacheState,
+    settings: &MessagesEventsSettings,
+    req: hide_message::Request,
+) -> Result<(), AppError> {
+    let message = messages::repo::get_message_by_id(db, req.message_id).await?;
+
+    let hidden_at = req.hidden.then(|| Utc::now().naive_utc());
+
+    // повторное скрытие не сдвигает hidden_at и не плодит эвенты
+    if message.hidden_at.is_some() == req.hidden {
+        return Ok(());
+    }
+
+    let (action, tp) = if req.hidden {
+        (AuditLogAction::Hidden, MessageType::Hidden)
+    } else {
+        (AuditLogAction::Unhidden, MessageType::Unhidden)
+    };
+
+    let tx = db.begin().await?;
+
+    let before = message.clone();
+    let message = repo::update_message_hidden_at(&tx, message, hidden_at).await?;
+
+    audit::record(&tx, req.current_user, action, Some(&before), Some(&message)).await?;
+
+    // текст стрима это копия корневого сообщения, скрывается и возвращается вместе с ним
+    let stream = match messages::repo::find_stream_by_message_id(&tx, message.message_id).await? {
+        Some(stream) => {
+            let text = if req.hidden {
+                String::new()
+            } else {
+                message.text.clone()
+            };
+
+            Some(repo::update_stream_text(&tx, stream, text).await?)
+        }
+        None => None,
+    };
+
+    tx.commit().await?;
+
+    cache
+        .messages
+        .invalidate(message.message_id, message.updated_at);
+
+    messages::events::message(js, settings, &message, tp).await?;
+
+    if let Some(stream) = stream {
+        cache
+            .streams
+            .invalidate(stream.message_id, stream.updated_at);
+        messages::events::stream(js, settings, &stream, StreamType::Updated).await?;
+    }
+
+    Ok(())
+}
+
+pub mod hide_message {
+    use uuid::Uuid;
+
+    use crate::app::current_user::CurrentUser;
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+        pub message_id: Uuid,
+        pub hidden: bool,
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn suspend_user(
+    db: &DbConn,
+    req: suspend_user::Request,
+) -> Result<suspend_user::Response, AppError> {
+    let now = Utc::now().naive_utc();
+
+    if req.until.is_some_and(|until| until <= now) {
+        return Err(AppError::Validation);
+    }
+
+    let tx = db.begin().await?;
+
+    let before = messages::repo::find_user_suspension(&tx, req.user_id).await?;
+
+    let user_suspension = repo::upsert_user_suspension(
+        &tx,
+        UserSuspensionModel {
+            user_suspension_id: Uuid::now_v7(),
+            user_id: req.user_id,
+            reason: req.reason,
+            until: req.until,
+            created_at: now,
+            updated_at: now,
+        },
+    )
+    .await?;
+
+    let action = if before.is_some() {
+        AuditLogAction::Updated
+    } else {
+        AuditLogAction::Created
+    };
+
+    audit::record(
+        &tx,
+        req.current_user,
+        action,
+        before.as_ref(),
+        Some(&user_suspension),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(suspend_user::Response { user_suspension })
+}
+
+pub mod suspend_user {
+    use chrono::NaiveDateTime;
+    use uuid::Uuid;
+
+    use crate::app::{admin::repo::UserSuspensionModel, current_user::CurrentUser};
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+        pub user_id: Uuid,
+        pub reason: Option<String>,
+        pub until: Option<NaiveDateTime>,
+    }
+
+    pub struct Response {
+        pub user_suspension: UserSuspensionModel,
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn unsuspend_user(db: &DbConn, req: unsuspend_user::Request) -> Result<(), AppError> {
+    let tx = db.begin().await?;
+
+    let Some(user_suspension) = messages::repo::find_user_suspension(&tx, req.user_id).await?
+    else {
+        return Ok(());
+    };
+
+    repo::delete_user_suspension(&tx, req.user_id).await?;
+
+    audit::record(
+        &tx,
+        req.current_user,
+        AuditLogAction::Deleted,
+        Some(&user_suspension),
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub mod unsuspend_user {
+    use uuid::Uuid;
+
+    use crate::app::current_user::CurrentUser;
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+        pub user_id: Uuid,
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn delete_topic(
+    db: &DbConn,
+    js: &Context,
+    cache: &CacheState,
+    messages_events: &MessagesEventsSettings,
+    topics_events: &TopicsEventsSettings,
+    req: delete_topic::Request,
+) -> Result<(), AppError> {
+    let topic = topics::repo::get_topic_by_id(db, req.topic_id).await?;
+
+    let tx = db.begin().await?;
+
+    let cascade = repo::delete_topic(&tx, topic.topic_id).await?;
+
+    audit::record(
+        &tx,
+        req.current_user,
+        AuditLogAction::Deleted,
+        Some(&topic),
+        None,
+    )
+    .await?;
+
+    for message_topic in &cascade.messages_topics {
+        audit::record(
+            &tx,
+            req.current_user,
+            AuditLogAction::Deleted,
+            Some(message_topic),
+            None,
+        )
+        .await?;
+    }
+
+    for topic_user in &cascade.topics_users {
+        audit::record(
+            &tx,
+            req.current_user,
+            AuditLogAction::Deleted,
+            Some(topic_user),
+            None,
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    // удаленный топик свежее уже не станет
+    cache.topics.invalidate(topic.topic_id, NaiveDateTime::MAX);
+
+    topics::events::topic(js, topics_events, &topic, TopicType::Deleted).await?;
+
+    // уведомления и тренды слушают строки, а не топик, поэтому эвент на каждую
+    for message_topic in &cascade.messages_topics {
+        messages::events::message_topic(
+            js,
+            messages_events,
+            message_topic,
+            MessageTopicType::Deleted,
+        )
+        .await?;
+    }
+
+    for topic_user in &cascade.topics_users {
+        metrics::topic_user_deleted();
+
+        topics::events::topic_user(js, topics_events, topic_user, TopicUserType::Deleted).await?;
+    }
+
+    Ok(())
+}
+
+pub mod delete_topic {
+    use uuid::Uuid;
+
+    use crate::app::current_user::CurrentUser;
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+        pub topic_id: Uuid,
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn delete_topic_user(
+    db: &DbConn,
+    js: &Context,
+    settings: &TopicsEventsSettings,
+    req: delete_topic_user::Request,
+) -> Result<(), AppError> {
+    let topic_user = topics::repo::get_topic_user_by_id(db, req.topic_user_id).await?;
+
+    let tx = db.begin().await?;
+
+    topics::repo::delete_topic_user(&tx, topic_user.clone()).await?;
+
+    audit::record(
+        &tx,
+        req.current_user,
+        AuditLogAction::Deleted,
+        Some(&topic_user),
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    metrics::topic_user_deleted();
+
+    topics::events::topic_user(js, settings, &topic_user, TopicUserType::Deleted).await?;
+
+    Ok(())
+}
+
+pub mod delete_topic_user {
+    use uuid::Uuid;
+
+    use crate::app::current_user::CurrentUser;
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+        pub topic_user_id: Uuid,
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn get_user_content(
+    db: &DbConn,
+    settings: &AdminSettings,
+    req: get_user_content::Request,
+) -> Result<get_user_content::Response, AppError> {
+    let limit = page::limit(req.page_size, settings.limits.messages);
+
+    let messages =
+        messages::repo::get_messages_by_user_id(db, req.user_id, req.page_token, limit + 1).await?;
+
+    let topics = topics::repo::get_topics_by_user_id(db, req.user_id).await?;
+    let topics_users = topics::repo::get_topics_users_by_user_id(db, req.user_id).await?;
+    let user_suspension = messages::repo::find_user_suspension(db, req.user_id).await?;
+
+    Ok(get_user_content::Response {
+        messages: Page::new(messages, limit, req.page_token, |it| it.message_id),
+        topics,
+        topics_users,
+        user_suspension,
+    })
+}
+
+pub mod get_user_content {
+    use uuid::Uuid;
+
+    use crate::app::{
+        admin::repo::UserSuspensionModel,
+        messages::{
+            page::{Page, PageToken},
+            repo::MessageModel,
+        },
+        topics::repo::{TopicModel, TopicUserModel},
+    };
+
+    pub struct Request {
+        pub user_id: Uuid,
+        pub page_token: Option<PageToken>,
+        pub page_size: Option<u64>,
+    }
+
+    pub struct Response {
+        pub messages: Page<MessageModel>,
+        pub topics: Vec<TopicModel>,
+        pub topics_users: Vec<TopicUserModel>,
+        pub user_suspension: Option<UserSuspensionModel>,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bzd_lib::error::Error;
+        use sea_orm::{DatabaseBackend, MockDatabase};
+        use uuid::Uuid;
+
+        use crate::app::{
+            admin::{
+                repo::UserSuspensionModel,
+                service::{self, get_user_content::Request},
+                settings::AdminSettings,
+            },
+            messages::repo::MessageModel,
+            topics::repo::{TopicModel, TopicUserModel},
+        };
+
+        #[tokio::test]
+        async fn test_ok_get_user_content() -> Result<(), Error> {
+            let settings = AdminSettings::stub();
+            let user_id = Uuid::now_v7();
+
+            let messages: Vec<MessageModel> = (0..=settings.limits.messages)
+                .map(|_| MessageModel::new(user_id, "TEXT".into(), "CODE".into()))
+                .rev()
+                .collect();
+
+            let req = Request {
+                user_id,
+                page_token: None,
+                page_size: None,
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([messages.clone()])
+                .append_query_results([Vec::<TopicModel>::new()])
+                .append_query_results([Vec::<TopicUserModel>::new()])
+                .append_query_results([Vec::<UserSuspensionModel>::new()])
+                .into_connection();
+
+            let res = service::get_user_content(&db, &settings, req).await?;
+
+            assert_eq!(res.messages.items.len() as u64, settings.limits.messages);
+            assert!(res.messages.older.is_some());
+            assert!(res.user_suspension.is_none());
+
+            Ok(())
+        }
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn get_audit_log(
+    db: &DbConn,
+    settings: &AdminSettings,
+    req: get_audit_log::Request,
+) -> Result<get_audit_log::Response, AppError> {
+    if req.from.zip(req.to).is_some_and(|(from, to)| from >= to) {
+        return Err(AppError::Validation);
+    }
+
+    let limit = page::limit(req.page_size, settings.limits.audit_log);
+
+    let audit_log = audit::repo::get_audit_log(
+        db,
+        audit::repo::Filter {
+            actor_id: req.actor_id,
+            entity_id: req.entity_id,
+            from: req.from,
+            to: req.to,
+        },
+        req.page_token,
+        limit + 1,
+    )
+    .await?;
+
+    Ok(get_audit_log::Response {
+        audit_log: Page::new(audit_log, limit, req.page_token, |it| it.audit_log_id),
+    })
+}
+
+pub mod get_audit_log {
+    use chrono::NaiveDateTime;
+    use uuid::Uuid;
+
+    use crate::app::{
+        audit::repo::AuditLogModel,
+        messages::page::{Page, PageToken},
+    };
+
+    pub struct Request {
+        pub actor_id: Option<Uuid>,
+        pub entity_id: Option<Uuid>,
+        pub from: Option<NaiveDateTime>,
+        pub to: Option<NaiveDateTime>,
+        pub page_token: Option<PageToken>,
+        pub page_size: Option<u64>,
+    }
+
+    pub struct Response {
+        pub audit_log: Page<AuditLogModel>,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bzd_lib::error::Error;
+        use chrono::{Duration, Utc};
+        use sea_orm::{DatabaseBackend, MockDatabase, Value};
+        use serde_json::json;
+        use uuid::Uuid;
+
+        use crate::app::{
+            admin::{
+                service::{self, get_audit_log::Request},
+                settings::AdminSettings,
+            },
+            audit::repo::{AuditLogAction, AuditLogKind, AuditLogModel},
+            error::AppError,
+        };
+
+        #[tokio::test]
+        async fn test_validation_get_audit_log() -> Result<(), Error> {
+            let settings = AdminSettings::stub();
+            let now = Utc::now().naive_utc();
+
+            let req = Request {
+                actor_id: None,
+                entity_id: None,
+                from: Some(now),
+                to: Some(now - Duration::hours(1)),
+                page_token: None,
+                page_size: None,
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+
+            let res = service::get_audit_log(&db, &settings, req).await;
+
+            assert!(matches!(res, Err(AppError::Validation)));
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_ok_get_audit_log() -> Result<(), Error> {
+            let settings = AdminSettings::stub();
+            let topic_id = Uuid::now_v7();
+
+            let audit_log: Vec<AuditLogModel> = (0..=settings.limits.audit_log)
+                .map(|_| AuditLogModel {
+                    audit_log_id: Uuid::now_v7(),
+                    actor_id: Some(Uuid::now_v7()),
+                    action: AuditLogAction::Deleted,
+                    entity: AuditLogKind::MessageTopic,
+                    entity_ids: vec![Uuid::now_v7(), Uuid::now_v7(), topic_id],
+                    before: Some(json!({ "topic_id": topic_id })),
+                    after: None,
+                    request_id: Some("REQUEST_ID".into()),
+                    created_at: Utc::now().naive_utc(),
+                })
+                .rev()
+                .collect();
+
+            let req = Request {
+                actor_id: None,
+                entity_id: Some(topic_id),
+                from: None,
+                to: None,
+                page_token: None,
+                page_size: None,
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([audit_log.clone()])
+                .into_connection();
+
+            let res = service::get_audit_log(&db, &settings, req).await?;
+
+            assert_eq!(res.audit_log.items.len() as u64, settings.limits.audit_log);
+            assert_eq!(res.audit_log.items.first(), audit_log.first());
+            assert!(res.audit_log.older.is_some());
+
+            let values = db
+                .into_transaction_log()
+                .iter()
+                .flat_map(|it| it.statements())
+                .flat_map(|it| it.values.clone().map(|it| it.0).unwrap_or_default())
+                .collect::<Vec<_>>();
+
+            assert!(values.contains(&Value::from(vec![topic_id])));
+
+            Ok(())
+        }
+    }
+}
+
+// Выгрузка бывает долгой и живёт в своей задаче, клиент вычитывает чанки из канала,
+// а ограниченный канал не даёт задаче убежать вперёд медленного клиента
+#[instrument(skip_all)]
+pub fn export_user_data(
+    db: Arc<DbConn>,
+    settings: AdminSettings,
+    req: export_user_data::Request,
+) -> Receiver<Result<export_user_data::Chunk, AppError>> {
+    let (mut sender, receiver) = mpsc::channel(1);
+
+    tokio::spawn(
+        async move {
+            if let Err(err) = export_sections(&db, &settings, req.user_id, &mut sender).await {
+                // клиент мог уже отключиться, тогда сообщать об ошибке некому
+                let _ = sender.send(Err(err)).await;
+            }
+        }
+        .in_current_span(),
+    );
+
+    receiver
+}
+
+async fn export_sections(
+    db: &DbConn,
+    settings: &AdminSettings,
+    user_id: Uuid,
+    sender: &mut Sender<Result<export_user_data::Chunk, AppError>>,
+) -> Result<(), AppError> {
+    use export_user_data::{Chunk, Manifest, Section};
+
+    // один снимок на всю выгрузку, иначе секции могут не сойтись между собой
+    let tx = db
+        .begin_with_config(
+            Some(IsolationLevel::RepeatableRead),
+            Some(AccessMode::ReadOnly),
+        )
+        .await?;
+
+    let mut exporter = Exporter {
+        tx: &tx,
+        sender,
+        batch: settings.limits.export,
+        sections: vec![],
+    };
+
+    exporter
+        .section(
+            Section::Messages,
+            repo::user_messages(user_id),
+            message::Column::MessageId,
+            |it| it.message_id,
+        )
+        .await?;
+
+    exporter
+        .section(
+            Section::Streams,
+            repo::user_streams(user_id),
+            stream::Column::StreamId,
+            |it| it.stream_id,
+        )
+        .await?;
+
+    exporter
+        .section(
+            Section::MessagesUsers,
+            repo::user_messages_users(user_id),
+            message_user::Column::MessageUserId,
+            |it| it.message_user_id,
+        )
+        .await?;
+
+    exporter
+        .section(
+            Section::Topics,
+            repo::user_topics(user_id),
+            topic::Column::TopicId,
+            |it| it.topic_id,
+        )
+        .await?;
+
+    exporter
+        .section(
+            Section::TopicsUsers,
+            repo::user_topics_users(user_id),
+            topic_user::Column::TopicUserId,
+            |it| it.topic_user_id,
+        )
+        .await?;
+
+    exporter
+        .section(
+            Section::MessagesTopics,
+            repo::user_messages_topics(user_id),
+            message_topic::Column::MessageTopicId,
+            |it| it.message_topic_id,
+        )
+        .await?;
+
+    let manifest = Manifest {
+        user_id,
+        sections: exporter.sections,
+        created_at: Utc::now().naive_utc(),
+    };
+
+    tx.commit().await?;
+
+    send(sender, Chunk::Manifest(manifest)).await
+}
+
+struct Exporter<'a> {
+    tx: &'a DatabaseTransaction,
+    sender: &'a mut Sender<Result<export_user_data::Chunk, AppError>>,
+    batch: u64,
+    sections: Vec<export_user_data::ManifestSection>,
+}
+
+impl Exporter<'_> {
+    async fn section<E: EntityTrait>(
+        &mut self,
+        section: export_user_data::Section,
+        query: Select<E>,
+        column: E::Column,
+        key: fn(&E::Model) -> Uuid,
+    ) -> Result<(), AppError>
+    where
+        E::Model: Serialize,
+    {
+        let mut after = None;
+        let mut count = 0;
+
+        loop {
+            let batch = repo::get_batch(self.tx, query.clone(), column, after, self.batch).await?;
+
+            let Some(last) = batch.last() else {
+                break;
+            };
+
+            after = Some(key(last));
+            count += batch.len() as u64;
+
+            let mut data = vec![];
+
+            for it in &batch {
+                serde_json::to_writer(&mut data, it)?;
+                data.push(b'\n');
+            }
+
+            send(
+                self.sender,
+                export_user_data::Chunk::Data {
+                    section,
+                    data: data.into(),
+                },
+            )
+            .await?;
+
+            if (batch.len() as u64) < self.batch {
+                break;
+            }
+        }
+
+        self.sections
+            .push(export_user_data::ManifestSection { section, count });
+
+        Ok(())
+    }
+}
+
+async fn send(
+    sender: &mut Sender<Result<export_user_data::Chunk, AppError>>,
+    chunk: export_user_data::Chunk,
+) -> Result<(), AppError> {
+    // получатель закрыт только если клиент ушёл, дальше выгружать незачем
+    sender
+        .send(Ok(chunk))
+        .await
+        .map_err(|_| AppError::Unreachable)
+}
+
+pub mod export_user_data {
+    use bytes::Bytes;
+    use chrono::NaiveDateTime;
+    use serde::Serialize;
+    use uuid::Uuid;
+
+    pub struct Request {
+        pub user_id: Uuid,
+    }
+
+    pub enum Chunk {
+        // NDJSON, по строке на запись
+        Data { section: Section, data: Bytes },
+        // всегда последний
+        Manifest(Manifest),
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Section {
+        Messages,
+        Streams,
+        MessagesUsers,
+        Topics,
+        TopicsUsers,
+        MessagesTopics,
+    }
+
+    impl Section {
+        pub fn name(&self) -> &'static str {
+            match self {
+                Self::Messages => "messages",
+                Self::Streams => "streams",
+                Self::MessagesUsers => "messages_users",
+                Self::Topics => "topics",
+                Self::TopicsUsers => "topics_users",
+                Self::MessagesTopics => "messages_topics",
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    pub struct Manifest {
+        pub user_id: Uuid,
+        pub sections: Vec<ManifestSection>,
+        pub created_at: NaiveDateTime,
+    }
+
+    #[derive(Serialize)]
+    pub struct ManifestSection {
+        pub section: Section,
+        pub count: u64,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::Arc;
+
+        use bzd_lib::error::Error;
+        use futures::StreamExt as _;
+        use sea_orm::{DatabaseBackend, MockDatabase};
+        use uuid::Uuid;
+
+        use crate::app::{
+            admin::{
+                service::{
+                    self,
+                    export_user_data::{Chunk, Request, Section},
+                },
+                settings::AdminSettings,
+            },
+            error::AppError,
+            messages::repo::{MessageModel, MessageTopicModel, MessageUserModel, StreamModel},
+            topics::repo::{TopicModel, TopicUserModel},
+        };
+
+        #[tokio::test]
+        async fn test_ok_export_user_data() -> Result<(), Error> {
+            let settings = AdminSettings::stub();
+            let user_id = Uuid::now_v7();
+
+            let messages: Vec<MessageModel> = (0..settings.limits.export)
+                .map(|_| MessageModel::new(user_id, "TEXT".into(), "CODE".into()))
+                .collect();
+
+            // полная пачка сообщений требует ещё одного запроса, остальные секции пустые
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([messages.clone()])
+                .append_query_results([Vec::<MessageModel>::new()])
+                .append_query_results([Vec::<StreamModel>::new()])
+                .append_query_results([Vec::<MessageUserModel>::new()])
+                .append_query_results([Vec::<TopicModel>::new()])
+                .append_query_results([Vec::<TopicUserModel>::new()])
+                .append_query_results([Vec::<MessageTopicModel>::new()])
+                .into_connection();
+
+            let chunks = service::export_user_data(Arc::new(db), settings, Request { user_id })
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let [Chunk::Data { section, data }, Chunk::Manifest(manifest)] = chunks.as_slice()
+            else {
+                return Err(AppError::Unreachable.into());
+            };
+
+            assert_eq!(*section, Section::Messages);
+            assert_eq!(data.split(|it| *it == b'\n').count() - 1, messages.len());
+            assert_eq!(manifest.sections.len(), 6);
+            assert_eq!(manifest.sections[0].count, messages.len() as u64);
+
+            Ok(())
+        }
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn delete_user_data(
+    db: &DbConn,
+    req: delete_user_data::Request,
+) -> Result<delete_user_data::Response, AppError> {
+    let tx = db.begin().await?;
+
+    let user_erasure = repo::upsert_user_erasure(&tx, UserErasureModel::new(req.user_id)).await?;
+
+    audit::record(
+        &tx,
+        req.current_user,
+        AuditLogAction::Created,
+        None,
+        Some(&user_erasure),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(delete_user_data::Response { user_erasure })
+}
+
+pub mod delete_user_data {
+    use uuid::Uuid;
+
+    use crate::app::{admin::repo::UserErasureModel, current_user::CurrentUser};
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+        pub user_id: Uuid,
+    }
+
+    pub struct Response {
+        pub user_erasure: UserErasureModel,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bzd_lib::error::Error;
+        use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+        use uuid::Uuid;
+
+        use crate::app::admin::{
+            repo::UserErasureModel,
+            service::{self, delete_user_data::Request},
+        };
+
+        #[tokio::test]
+        async fn test_ok_delete_user_data() -> Result<(), Error> {
+            let user_erasure = UserErasureModel::new(Uuid::now_v7());
+
+            let req = Request {
+                current_user: None,
+                user_id: user_erasure.user_id,
+            };
+
+            let exec = || MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_exec_results([exec()])
+                .append_query_results([vec![user_erasure.clone()]])
+                .append_exec_results([exec()])
+                .into_connection();
+
+            let res = service::delete_user_data(&db, req).await?;
+
+            assert_eq!(res.user_erasure, user_erasure);
+            assert!(res.user_erasure.completed_at.is_none());
+
+            Ok(())
+        }
+    }
+}
+
+// Удаление идет пачками в отдельных транзакциях и переживает рестарт: незавершенные
+// подбирает воркер, а каждая пачка видит только то, что еще не стерто. Эвенты пишутся в outbox
+// той же транзакцией, поэтому продолжение удаления продолжает и их отправку
+#[instrument(skip_all)]
+pub async fn erase_user_data(
+    db: &DbConn,
+    js: &Context,
+    cache: &CacheState,
+    settings: &AdminSettings,
+    messages_events: &MessagesEventsSettings,
+    topics_events: &TopicsEventsSettings,
+) -> Result<(), AppError> {
+    // хвост прошлого запуска, если отправка тогда упала
+    events::relay(db, js, settings.erasure.batch).await?;
+
+    let user_erasures = repo::get_pending_user_erasures(db, settings.erasure.batch).await?;
+
+    for user_erasure in &user_erasures {
+        while erase_messages(db, cache, settings, messages_events, user_erasure).await? {}
+        while erase_topics(
+            db,
+            cache,
+            settings,
+            messages_events,
+            topics_events,
+            user_erasure,
+        )
+        .await?
+        {}
+
+        erase_rest(db, topics_events, user_erasure).await?;
+    }
+
+    events::relay(db, js, settings.erasure.batch).await?;
+
+    Ok(())
+}
+
+// Сообщения становятся надгробиями, стрим и его связи остаются на месте
+async fn erase_messages(
+    db: &DbConn,
+    cache: &CacheState,
+    settings: &AdminSettings,
+    messages_events: &MessagesEventsSettings,
+    user_erasure: &UserErasureModel,
+) -> Result<bool, AppError> {
+    let tx = db.begin().await?;
+
+    let Some(user_erasure) = repo::lock_user_erasure(&tx, user_erasure.user_erasure_id).await?
+    else {
+        return Ok(false);
+    };
+
+    let messages = repo::get_batch(
+        &tx,
+        repo::user_messages(user_erasure.user_id),
+        message::Column::MessageId,
+        None,
+        settings.erasure.batch,
+    )
+    .await?;
+
+    if messages.is_empty() {
+        return Ok(false);
+    }
+
+    let message_ids: Vec<Uuid> = messages.iter().map(|it| it.message_id).collect();
+
+    let messages_topics =
+        repo::get_messages_topics_by_message_ids(&tx, message_ids.clone()).await?;
+    repo::delete_messages_topics_by_message_ids(&tx, message_ids.clone()).await?;
+
+    let messages_streams =
+        repo::get_messages_streams_by_message_ids(&tx, message_ids.clone()).await?;
+    let stream_ids: Vec<Uuid> = messages_streams.iter().map(|it| it.stream_id).collect();
+
+    for stream in repo::get_streams_by_ids(&tx, stream_ids.clone()).await? {
+        // корень стрима ответом не считается, его надгробие только стирает копию текста
+        let replies = messages_streams
+            .iter()
+            .filter(|it| it.stream_id == stream.stream_id && it.message_id != stream.message_id)
+            .count() as i64;
+        let is_root = message_ids.contains(&stream.message_id);
+
+        repo::erase_stream(&tx, stream.stream_id, replies, is_root).await?;
+    }
+
+    let mut tombstones = vec![];
+
+    for message in messages {
+        let tombstone = repo::tombstone_message(&tx, message).await?;
+
+        audit::record_ids(&tx, None, AuditLogAction::Deleted, &tombstone).await?;
+        events::enqueue(
+            &tx,
+            messages::events::message_event(messages_events, &tombstone, MessageType::Deleted)?,
+        )
+        .await?;
+
+        tombstones.push(tombstone);
+    }
+
+    for message_topic in &messages_topics {
+        audit::record_ids(&tx, None, AuditLogAction::Deleted, message_topic).await?;
+        events::enqueue(
+            &tx,
+            messages::events::message_topic_event(
+                messages_events,
+                message_topic,
+                MessageTopicType::Deleted,
+            )?,
+        )
+        .await?;
+    }
+
+    let streams = repo::get_streams_by_ids(&tx, stream_ids).await?;
+
+    for stream in &streams {
+        events::enqueue(
+            &tx,
+            messages::events::stream_event(messages_events, stream, StreamType::Updated)?,
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    for message in &tombstones {
+        cache
+            .messages
+            .invalidate(message.message_id, message.updated_at);
+    }
+
+    for stream in &streams {
+        cache
+            .streams
+            .invalidate(stream.message_id, stream.updated_at);
+    }
+
+    Ok(true)
+}
+
+async fn erase_topics(
+    db: &DbConn,
+    cache: &CacheState,
+    settings: &AdminSettings,
+    messages_events: &MessagesEventsSettings,
+    topics_events: &TopicsEventsSettings,
+    user_erasure: &UserErasureModel,
+) -> Result<bool, AppError> {
+    let tx = db.begin().await?;
+
+    let Some(user_erasure) = repo::lock_user_erasure(&tx, user_erasure.user_erasure_id).await?
+    else {
+        return Ok(false);
+    };
+
+    let topics = repo::get_batch(
+        &tx,
+        repo::user_topics(user_erasure.user_id),
+        topic::Column::TopicId,
+        None,
+        settings.erasure.batch,
+    )
+    .await?;
+
+    if topics.is_empty() {
+        return Ok(false);
+    }
+
+    let mut topics_users_count = 0;
+
+    for topic in &topics {
+        let cascade = repo::delete_topic(&tx, topic.topic_id).await?;
+        topics_users_count += cascade.topics_users.len();
+
+        audit::record_ids(&tx, None, AuditLogAction::Deleted, topic).await?;
+        events::enqueue(
+            &tx,
+            topics::events::topic_event(topics_events, topic, TopicType::Deleted)?,
+        )
+        .await?;
+
+        // подписки и привязки других пользователей уходят вместе с топиком
+        for message_topic in &cascade.messages_topics {
+            audit::record_ids(&tx, None, AuditLogAction::Deleted, message_topic).await?;
+            events::enqueue(
+                &tx,
+                messages::events::message_topic_event(
+                    messages_events,
+                    message_topic,
+                    MessageTopicType::Deleted,
+                )?,
+            )
+            .await?;
+        }
+
+        for topic_user in &cascade.topics_users {
+            audit::record_ids(&tx, None, AuditLogAction::Deleted, topic_user).await?;
+            events::enqueue(
+                &tx,
+                topics::events::topic_user_event(
+                    topics_events,
+                    topic_user,
+                    TopicUserType::Deleted,
+                )?,
+            )
+            .await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    for topic in &topics {
+        cache.topics.invalidate(topic.topic_id, NaiveDateTime::MAX);
+    }
+
+    for _ in 0..topics_users_count {
+        metrics::topic_user_deleted();
+    }
+
+    Ok(true)
+}
+
+// Подписки, участие в стримах, блокировки, передачи и бан, после чего удаление завершено
+async fn erase_rest(
+    db: &DbConn,
+    topics_events: &TopicsEventsSettings,
+    user_erasure: &UserErasureModel,
+) -> Result<(), AppError> {
+    let tx = db.begin().await?;
+
+    let Some(user_erasure) = repo::lock_user_erasure(&tx, user_erasure.user_erasure_id).await?
+    else {
+        return Ok(());
+    };
+
+    let topics_users = repo::get_topics_users_by_user_id(&tx, user_erasure.user_id).await?;
+
+    repo::delete_user_rows(&tx, user_erasure.user_id).await?;
+
+    for topic_user in &topics_users {
+        audit::record_ids(&tx, None, AuditLogAction::Deleted, topic_user).await?;
+        events::enqueue(
+            &tx,
+            topics::events::topic_user_event(topics_events, topic_user, TopicUserType::Deleted)?,
+        )
+        .await?;
+    }
+
+    // после всех пачек, чтобы затереть и снимки, записанные между ними
+    audit::repo::redact_audit_log(&tx, user_erasure.user_id).await?;
+
+    let before = user_erasure.clone();
+    let user_erasure = repo::complete_user_erasure(&tx, user_erasure).await?;
+
+    audit::record(
+        &tx,
+        None,
+        AuditLogAction::Updated,
+        Some(&before),
+        Some(&user_erasure),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    for _ in &topics_users {
+        metrics::topic_user_deleted();
+    }
+
+    Ok(())
+}
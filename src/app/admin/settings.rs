@@ -0,0 +1,43 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct AdminSettings {
+    pub limits: LimitsSettings,
+    pub erasure: ErasureSettings,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LimitsSettings {
+    pub messages: u64,
+    pub audit_log: u64,
+    // размер пачки строк в одном чанке выгрузки
+    pub export: u64,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ErasureSettings {
+    // как часто воркер подбирает незавершенные удаления, в секундах
+    pub interval: u64,
+    // сколько сообщений или топиков обрабатывается одной транзакцией
+    pub batch: u64,
+    // входящие эвенты об удалении аккаунта, пустой список отключает консьюмер
+    pub subjects: Vec<String>,
+}
+
+#[cfg(test)]
+impl AdminSettings {
+    pub fn stub() -> Self {
+        Self {
+            limits: LimitsSettings {
+                messages: 2,
+                audit_log: 2,
+                export: 2,
+            },
+            erasure: ErasureSettings {
+                interval: 1,
+                batch: 2,
+                subjects: vec![],
+            },
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use crate::app::{
+    admin::settings::AdminSettings, cache::CacheState, db::DbState, mess::MessState,
+    messages::settings::EventsSettings as MessagesEventsSettings,
+    topics::settings::EventsSettings as TopicsEventsSettings,
+};
+
+#[derive(Clone)]
+pub struct AdminState {
+    pub settings: AdminSettings,
+    pub db: DbState,
+    pub mess: MessState,
+    pub cache: CacheState,
+    // модерация публикует эвенты от имени messages и topics
+    pub messages_events: MessagesEventsSettings,
+    pub topics_events: TopicsEventsSettings,
+}
@@ -0,0 +1,198 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use chrono::Utc;
+use sea_orm::ConnectionTrait;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::app::{
+    admin::repo::UserErasureModel,
+    audit::repo::{AuditLogAction, AuditLogKind, AuditLogModel},
+    current_user::CurrentUser,
+    error::AppError,
+    messages::repo::{
+        MessageModel, MessageStreamModel, MessageTopicModel, MessageUserModel, StreamModel,
+        UserSuspensionModel,
+    },
+    topics::repo::{TopicModel, TopicTransferModel, TopicUserModel},
+};
+
+pub mod repo;
+
+const REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: String;
+}
+
+// x-request-id от балансера пробрасываем как есть, иначе заводим свой и отдаем клиенту
+pub async fn request_id(req: Request, next: Next) -> Response {
+    let request_id = req
+        .headers()
+        .get(&REQUEST_ID)
+        .and_then(|it| it.to_str().ok())
+        .filter(|it| !it.is_empty())
+        .map_or_else(|| Uuid::now_v7().to_string(), ToString::to_string);
+
+    let mut res = CURRENT_REQUEST_ID
+        .scope(request_id.clone(), next.run(req))
+        .await;
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        res.headers_mut().insert(REQUEST_ID, value);
+    }
+
+    res
+}
+
+pub trait Audited: Serialize {
+    const KIND: AuditLogKind;
+
+    fn entity_ids(&self) -> Vec<Uuid>;
+}
+
+// Пишется в той же транзакции, что и само изменение, чтобы журнал не расходился с данными
+pub async fn record<T: ConnectionTrait, M: Audited>(
+    db: &T,
+    actor: Option<CurrentUser>,
+    action: AuditLogAction,
+    before: Option<&M>,
+    after: Option<&M>,
+) -> Result<(), AppError> {
+    let entity_ids = after
+        .or(before)
+        .map(Audited::entity_ids)
+        .unwrap_or_default();
+
+    create(
+        db,
+        actor,
+        action,
+        M::KIND,
+        entity_ids,
+        before.map(serde_json::to_value).transpose()?,
+        after.map(serde_json::to_value).transpose()?,
+    )
+    .await
+}
+
+// Только факт и id: удаление данных пользователя не должно возвращать в журнал то, что стираем,
+// а снимок стрима несет копию чужого текста, до которой такое удаление не дотянется
+pub async fn record_ids<T: ConnectionTrait, M: Audited>(
+    db: &T,
+    actor: Option<CurrentUser>,
+    action: AuditLogAction,
+    model: &M,
+) -> Result<(), AppError> {
+    create(db, actor, action, M::KIND, model.entity_ids(), None, None).await
+}
+
+async fn create<T: ConnectionTrait>(
+    db: &T,
+    actor: Option<CurrentUser>,
+    action: AuditLogAction,
+    entity: AuditLogKind,
+    entity_ids: Vec<Uuid>,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+) -> Result<(), AppError> {
+    repo::create_audit_log(
+        db,
+        AuditLogModel {
+            audit_log_id: Uuid::now_v7(),
+            actor_id: actor.map(|it| it.user_id),
+            action,
+            entity,
+            entity_ids,
+            before,
+            after,
+            request_id: CURRENT_REQUEST_ID.try_with(Clone::clone).ok(),
+            created_at: Utc::now().naive_utc(),
+        },
+    )
+    .await
+}
+
+impl Audited for MessageModel {
+    const KIND: AuditLogKind = AuditLogKind::Message;
+
+    fn entity_ids(&self) -> Vec<Uuid> {
+        vec![self.message_id]
+    }
+}
+
+impl Audited for MessageTopicModel {
+    const KIND: AuditLogKind = AuditLogKind::MessageTopic;
+
+    fn entity_ids(&self) -> Vec<Uuid> {
+        vec![self.message_topic_id, self.message_id, self.topic_id]
+    }
+}
+
+impl Audited for MessageStreamModel {
+    const KIND: AuditLogKind = AuditLogKind::MessageStream;
+
+    fn entity_ids(&self) -> Vec<Uuid> {
+        vec![self.message_stream_id, self.message_id, self.stream_id]
+    }
+}
+
+impl Audited for MessageUserModel {
+    const KIND: AuditLogKind = AuditLogKind::MessageUser;
+
+    fn entity_ids(&self) -> Vec<Uuid> {
+        vec![self.message_user_id, self.message_id]
+    }
+}
+
+impl Audited for StreamModel {
+    const KIND: AuditLogKind = AuditLogKind::Stream;
+
+    fn entity_ids(&self) -> Vec<Uuid> {
+        vec![self.stream_id, self.message_id]
+    }
+}
+
+impl Audited for TopicModel {
+    const KIND: AuditLogKind = AuditLogKind::Topic;
+
+    fn entity_ids(&self) -> Vec<Uuid> {
+        vec![self.topic_id]
+    }
+}
+
+impl Audited for TopicUserModel {
+    const KIND: AuditLogKind = AuditLogKind::TopicUser;
+
+    fn entity_ids(&self) -> Vec<Uuid> {
+        vec![self.topic_user_id, self.topic_id]
+    }
+}
+
+impl Audited for TopicTransferModel {
+    const KIND: AuditLogKind = AuditLogKind::TopicTransfer;
+
+    fn entity_ids(&self) -> Vec<Uuid> {
+        vec![self.topic_transfer_id, self.topic_id]
+    }
+}
+
+impl Audited for UserSuspensionModel {
+    const KIND: AuditLogKind = AuditLogKind::UserSuspension;
+
+    fn entity_ids(&self) -> Vec<Uuid> {
+        vec![self.user_suspension_id, self.user_id]
+    }
+}
+
+impl Audited for UserErasureModel {
+    const KIND: AuditLogKind = AuditLogKind::UserErasure;
+
+    fn entity_ids(&self) -> Vec<Uuid> {
+        vec![self.user_erasure_id, self.user_id]
+    }
+}
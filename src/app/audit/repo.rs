@@ -0,0 +1,138 @@
+use chrono::NaiveDateTime;
+use sea_orm::{
+    ColumnTrait as _, ConnectionTrait, EntityTrait as _, IntoActiveModel as _, QueryFilter as _,
+    Value,
+    prelude::{Expr, Json},
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app::{
+    error::AppError,
+    messages::page::{PageToken, paginate},
+};
+
+pub mod audit_log;
+
+pub type AuditLogModel = audit_log::Model;
+pub type AuditLogAction = audit_log::Action;
+pub type AuditLogKind = audit_log::Kind;
+
+const REDACTED: &str = r#"
+(entity_ids @> $1
+OR $2 IN (before->>'user_id', after->>'user_id', before->>'to_user_id', after->>'to_user_id'))
+"#;
+
+// Журнал только дописывается, исключение одно: redact_audit_log при удалении данных пользователя
+#[instrument(skip_all)]
+pub async fn create_audit_log<T: ConnectionTrait>(
+    db: &T,
+    model: AuditLogModel,
+) -> Result<(), AppError> {
+    audit_log::Entity::insert(model.into_active_model())
+        .exec_without_returning(db)
+        .await?;
+
+    Ok(())
+}
+
+pub struct Filter {
+    pub actor_id: Option<Uuid>,
+    pub entity_id: Option<Uuid>,
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+}
+
+#[instrument(skip_all)]
+pub async fn get_audit_log<T: ConnectionTrait>(
+    db: &T,
+    filter: Filter,
+    page_token: Option<PageToken>,
+    limit: u64,
+) -> Result<Vec<AuditLogModel>, AppError> {
+    let mut query = audit_log::Entity::find();
+
+    if let Some(actor_id) = filter.actor_id {
+        query = query.filter(audit_log::Column::ActorId.eq(actor_id));
+    }
+
+    if let Some(entity_id) = filter.entity_id {
+        // массивом в параметре: внутри ARRAY[...] плейсхолдер sea-query не подставляет
+        query = query.filter(Expr::cust_with_values(
+            "entity_ids @> $1",
+            [vec![entity_id]],
+        ));
+    }
+
+    if let Some(from) = filter.from {
+        query = query.filter(audit_log::Column::CreatedAt.gte(from));
+    }
+
+    if let Some(to) = filter.to {
+        query = query.filter(audit_log::Column::CreatedAt.lt(to));
+    }
+
+    let audit_log = paginate(query, audit_log::Column::AuditLogId, page_token, limit)
+        .all(db)
+        .await?;
+
+    Ok(audit_log)
+}
+
+// Снимки хранят тексты, названия и причины банов, поэтому при удалении данных пользователя
+// затираются все записи о нем: по id в entity_ids (баны, удаление) и по автору, владельцу или
+// получателю в самом снимке, в том числе для строк, удаленных задолго до этого.
+// Сама запись о том, кто и когда что делал, остается
+#[instrument(skip_all)]
+pub async fn redact_audit_log<T: ConnectionTrait>(db: &T, user_id: Uuid) -> Result<(), AppError> {
+    audit_log::Entity::update_many()
+        .col_expr(audit_log::Column::Before, Expr::value(Option::<Json>::None))
+        .col_expr(audit_log::Column::After, Expr::value(Option::<Json>::None))
+        .filter(Expr::cust_with_values(
+            REDACTED,
+            [Value::from(vec![user_id]), Value::from(user_id.to_string())],
+        ))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bzd_lib::error::Error;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Value};
+    use uuid::Uuid;
+
+    use crate::app::audit::repo::redact_audit_log;
+
+    #[tokio::test]
+    async fn test_ok_redact_audit_log() -> Result<(), Error> {
+        let user_id = Uuid::now_v7();
+
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 3,
+            }])
+            .into_connection();
+
+        redact_audit_log(&db, user_id).await?;
+
+        let log = db.into_transaction_log();
+        let statement = log
+            .first()
+            .and_then(|it| it.statements().first().cloned())
+            .ok_or(crate::app::error::AppError::Unreachable)?;
+
+        // все виды записей, без фильтра по entity
+        assert!(!statement.sql.contains("\"entity\" ="));
+        assert!(statement.sql.contains("before->>'to_user_id'"));
+
+        let values = statement.values.map(|it| it.0).unwrap_or_default();
+        assert!(values.contains(&Value::from(vec![user_id])));
+        assert!(values.contains(&Value::from(user_id.to_string())));
+
+        Ok(())
+    }
+}
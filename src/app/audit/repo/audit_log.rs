@@ -0,0 +1,62 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "audit_logs")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub audit_log_id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub action: Action,
+    pub entity: Kind,
+    // первым идет id самой сущности, дальше те, на которые она ссылается
+    pub entity_ids: Vec<Uuid>,
+    pub before: Option<Json>,
+    pub after: Option<Json>,
+    pub request_id: Option<String>,
+    pub created_at: DateTime,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum Action {
+    #[sea_orm(string_value = "created")]
+    Created,
+    #[sea_orm(string_value = "updated")]
+    Updated,
+    #[sea_orm(string_value = "deleted")]
+    Deleted,
+    #[sea_orm(string_value = "hidden")]
+    Hidden,
+    #[sea_orm(string_value = "unhidden")]
+    Unhidden,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum Kind {
+    #[sea_orm(string_value = "message")]
+    Message,
+    #[sea_orm(string_value = "message_topic")]
+    MessageTopic,
+    #[sea_orm(string_value = "message_stream")]
+    MessageStream,
+    #[sea_orm(string_value = "message_user")]
+    MessageUser,
+    #[sea_orm(string_value = "stream")]
+    Stream,
+    #[sea_orm(string_value = "topic")]
+    Topic,
+    #[sea_orm(string_value = "topic_user")]
+    TopicUser,
+    #[sea_orm(string_value = "topic_transfer")]
+    TopicTransfer,
+    #[sea_orm(string_value = "user_suspension")]
+    UserSuspension,
+    #[sea_orm(string_value = "user_erasure")]
+    UserErasure,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,416 @@
+use std::sync::Arc;
+
+use axum::http::HeaderMap;
+use bzd_lib::error::Error;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, errors::ErrorKind};
+use serde::Deserialize;
+use subtle::ConstantTimeEq as _;
+use tonic::{Request, Status, metadata::MetadataMap, service::Interceptor};
+
+use crate::app::{auth::settings::AuthSettings, current_user::CurrentUser, error::AppError};
+
+pub mod settings;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Auth {
+    User(CurrentUser),
+    Admin(CurrentUser),
+    Internal,
+    Anonymous,
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    sub: String,
+    #[serde(default)]
+    roles: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct AuthInterceptor {
+    key: Arc<DecodingKey>,
+    validation: Arc<Validation>,
+    internal_key: Option<String>,
+    admin_role: Option<String>,
+}
+
+impl AuthInterceptor {
+    pub fn new(settings: &AuthSettings) -> Result<Self, Error> {
+        // пустой секрет подписал бы любой, лучше не стартовать
+        if settings.key.trim().is_empty() {
+            return Err(jsonwebtoken::errors::Error::from(ErrorKind::InvalidKeyFormat).into());
+        }
+
+        let key = match settings.algorithm {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                DecodingKey::from_secret(settings.key.as_bytes())
+            }
+            Algorithm::ES256 | Algorithm::ES384 => {
+                DecodingKey::from_ec_pem(settings.key.as_bytes())?
+            }
+            Algorithm::EdDSA => DecodingKey::from_ed_pem(settings.key.as_bytes())?,
+            _ => DecodingKey::from_rsa_pem(settings.key.as_bytes())?,
+        };
+
+        let mut validation = Validation::new(settings.algorithm);
+
+        if let Some(issuer) = &settings.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+
+        if let Some(audience) = &settings.audience {
+            validation.set_audience(&[audience]);
+        }
+
+        Ok(Self {
+            key: Arc::new(key),
+            validation: Arc::new(validation),
+            internal_key: settings
+                .internal_key
+                .clone()
+                .filter(|it| !it.trim().is_empty()),
+            admin_role: settings.admin_role.clone(),
+        })
+    }
+
+    // для JSON шлюза: gRPC метаданные это те же http заголовки
+    pub fn authenticate(&self, headers: &HeaderMap) -> Result<Auth, AppError> {
+        self.auth(&MetadataMap::from_headers(headers.clone()))
+            .map_err(|_| AppError::Unauthenticated)
+    }
+
+    fn auth(&self, metadata: &MetadataMap) -> Result<Auth, Status> {
+        let token = metadata
+            .get("authorization")
+            .and_then(|it| it.to_str().ok())
+            .and_then(|it| it.strip_prefix("Bearer "));
+
+        if let Some(token) = token {
+            let claims = jsonwebtoken::decode::<Claims>(token, &self.key, &self.validation)
+                .map_err(|_| Status::unauthenticated("UNAUTHENTICATED"))?
+                .claims;
+
+            let user_id = claims
+                .sub
+                .parse()
+                .map_err(|_| Status::unauthenticated("UNAUTHENTICATED"))?;
+
+            let current_user = CurrentUser { user_id };
+
+            let is_admin = self
+                .admin_role
+                .as_ref()
+                .is_some_and(|role| claims.roles.contains(role));
+
+            return Ok(if is_admin {
+                Auth::Admin(current_user)
+            } else {
+                Auth::User(current_user)
+            });
+        }
+
+        let internal_key = metadata
+            .get("x-internal-key")
+            .and_then(|it| it.to_str().ok());
+
+        // сравнение за постоянное время, чтобы ключ нельзя было подобрать по задержке ответа
+        match (internal_key, &self.internal_key) {
+            (Some(internal_key), Some(key))
+                if bool::from(internal_key.as_bytes().ct_eq(key.as_bytes())) =>
+            {
+                Ok(Auth::Internal)
+            }
+            (Some(_), _) => Err(Status::unauthenticated("UNAUTHENTICATED")),
+            _ => Ok(Auth::Anonymous),
+        }
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut req: Request<()>) -> Result<Request<()>, Status> {
+        let auth = self.auth(req.metadata())?;
+        req.extensions_mut().insert(auth);
+
+        Ok(req)
+    }
+}
+
+// AdminService пускает только модераторов, internal_key сюда не подходит: внутренние сервисы
+// ставят удаление через эвент, а не через админский API
+#[derive(Clone)]
+pub struct AdminInterceptor {
+    auth: AuthInterceptor,
+}
+
+impl AdminInterceptor {
+    pub fn new(auth: AuthInterceptor) -> Self {
+        Self { auth }
+    }
+}
+
+impl Interceptor for AdminInterceptor {
+    fn call(&mut self, req: Request<()>) -> Result<Request<()>, Status> {
+        let req = self.auth.call(req)?;
+
+        match req.extensions().get::<Auth>() {
+            Some(Auth::Admin(_)) => Ok(req),
+            Some(Auth::User(_) | Auth::Internal) => Err(Status::permission_denied("FORBIDDEN")),
+            _ => Err(Status::unauthenticated("UNAUTHENTICATED")),
+        }
+    }
+}
+
+pub trait WithCurrentUserId {
+    fn current_user_id_mut(&mut self) -> &mut Option<String>;
+}
+
+// current_user_id из тела запроса доверяем только внутренним сервисам, остальным подставляем
+// пользователя из токена, поэтому TryFrom конверсии в grpc модулях остаются как есть
+pub fn into_inner<T: WithCurrentUserId>(req: Request<T>) -> T {
+    let auth = req
+        .extensions()
+        .get::<Auth>()
+        .copied()
+        .unwrap_or(Auth::Anonymous);
+
+    apply(auth, req.into_inner())
+}
+
+pub fn apply<T: WithCurrentUserId>(auth: Auth, mut inner: T) -> T {
+    match auth {
+        Auth::User(current_user) | Auth::Admin(current_user) => {
+            *inner.current_user_id_mut() = Some(current_user.user_id.to_string())
+        }
+        Auth::Internal => {}
+        Auth::Anonymous => *inner.current_user_id_mut() = None,
+    }
+
+    inner
+}
+
+macro_rules! with_current_user_id {
+    ($($tp:ty),* $(,)?) => {
+        $(
+            impl WithCurrentUserId for $tp {
+                fn current_user_id_mut(&mut self) -> &mut Option<String> {
+                    &mut self.current_user_id
+                }
+            }
+        )*
+    };
+}
+
+with_current_user_id!(
+    bzd_messages_api::messages::CreateMessageRequest,
+    bzd_messages_api::messages::GetMessagesRequest,
+    bzd_messages_api::messages::GetMessageMessagesRequest,
+    bzd_messages_api::messages::GetTopicMessagesRequest,
+    bzd_messages_api::messages::CreateMessageTopicRequest,
+    bzd_messages_api::messages::DeleteMessageTopicRequest,
+    bzd_messages_api::messages::SetMessageTopicsRequest,
+    bzd_messages_api::topics::CreateTopicRequest,
+    bzd_messages_api::topics::GetUserTopicsUsersRequest,
+    bzd_messages_api::topics::CreateTopicUserRequest,
+    bzd_messages_api::topics::UpdateTopicUserRequest,
+    bzd_messages_api::topics::DeleteTopicUserRequest,
+    bzd_messages_api::topics::TransferTopicRequest,
+    bzd_messages_api::topics::AcceptTopicTransferRequest,
+    bzd_messages_api::topics::DeleteTopicTransferRequest,
+    bzd_messages_api::topics::GetUserTopicsTransfersRequest,
+    bzd_messages_api::users::BlockUserRequest,
+    bzd_messages_api::users::UnblockUserRequest,
+    bzd_messages_api::users::GetBlockedUsersRequest,
+    bzd_messages_api::admin::HideMessageRequest,
+    bzd_messages_api::admin::UnhideMessageRequest,
+    bzd_messages_api::admin::SuspendUserRequest,
+    bzd_messages_api::admin::UnsuspendUserRequest,
+    bzd_messages_api::admin::DeleteTopicRequest,
+    bzd_messages_api::admin::DeleteTopicUserRequest,
+    bzd_messages_api::admin::GetUserContentRequest,
+    bzd_messages_api::admin::GetAuditLogRequest,
+    bzd_messages_api::admin::ExportUserDataRequest,
+    bzd_messages_api::admin::DeleteUserDataRequest,
+);
+
+#[cfg(test)]
+mod tests {
+    use bzd_lib::error::Error;
+    use bzd_messages_api::topics::CreateTopicRequest;
+    use jsonwebtoken::{Algorithm, EncodingKey, Header};
+    use serde::Serialize;
+    use tonic::{Request, service::Interceptor as _};
+    use uuid::Uuid;
+
+    use crate::app::auth::{self, AdminInterceptor, AuthInterceptor, settings::AuthSettings};
+
+    #[derive(Serialize)]
+    struct Claims {
+        sub: String,
+        exp: u64,
+        roles: Vec<String>,
+    }
+
+    fn settings() -> AuthSettings {
+        AuthSettings {
+            algorithm: Algorithm::HS256,
+            key: "SECRET".into(),
+            issuer: None,
+            audience: None,
+            internal_key: Some("INTERNAL".into()),
+            admin_role: Some("admin".into()),
+        }
+    }
+
+    fn token(sub: String, key: &str) -> Result<String, Error> {
+        role_token(sub, key, vec![])
+    }
+
+    fn role_token(sub: String, key: &str, roles: Vec<String>) -> Result<String, Error> {
+        let claims = Claims {
+            sub,
+            exp: u64::MAX / 2,
+            roles,
+        };
+
+        Ok(jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(key.as_bytes()),
+        )?)
+    }
+
+    fn call(
+        metadata: &[(&'static str, String)],
+        current_user_id: Option<String>,
+    ) -> Result<CreateTopicRequest, Error> {
+        let mut interceptor = AuthInterceptor::new(&settings())?;
+
+        let mut req = Request::new(());
+        for (key, value) in metadata {
+            req.metadata_mut().insert(*key, value.parse()?);
+        }
+
+        let req = interceptor.call(req)?;
+        let (metadata, extensions, _) = req.into_parts();
+
+        let req = Request::from_parts(
+            metadata,
+            extensions,
+            CreateTopicRequest {
+                current_user_id,
+                title: None,
+            },
+        );
+
+        Ok(auth::into_inner(req))
+    }
+
+    #[test]
+    fn successfully_inject_token_user() -> Result<(), Error> {
+        let user_id = Uuid::now_v7();
+
+        let req = call(
+            &[(
+                "authorization",
+                format!("Bearer {}", token(user_id.to_string(), "SECRET")?),
+            )],
+            Some(Uuid::now_v7().to_string()),
+        )?;
+
+        assert_eq!(req.current_user_id, Some(user_id.to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn failed_wrong_token() -> Result<(), Error> {
+        let user_id = Uuid::now_v7();
+
+        let req = call(
+            &[(
+                "authorization",
+                format!("Bearer {}", token(user_id.to_string(), "WRONG")?),
+            )],
+            None,
+        );
+
+        assert!(req.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn successfully_trust_internal_caller() -> Result<(), Error> {
+        let user_id = Uuid::now_v7().to_string();
+
+        let req = call(
+            &[("x-internal-key", "INTERNAL".into())],
+            Some(user_id.clone()),
+        )?;
+
+        assert_eq!(req.current_user_id, Some(user_id));
+
+        Ok(())
+    }
+
+    #[test]
+    fn failed_empty_key() {
+        let settings = AuthSettings {
+            key: " ".into(),
+            ..settings()
+        };
+
+        assert!(AuthInterceptor::new(&settings).is_err());
+    }
+
+    #[test]
+    fn failed_internal_caller_without_internal_key() -> Result<(), Error> {
+        let settings = AuthSettings {
+            internal_key: Some(String::new()),
+            ..settings()
+        };
+        let mut interceptor = AuthInterceptor::new(&settings)?;
+
+        let mut req = Request::new(());
+        req.metadata_mut().insert("x-internal-key", "".parse()?);
+
+        assert!(interceptor.call(req).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn successfully_drop_anon_current_user_id() -> Result<(), Error> {
+        let req = call(&[], Some(Uuid::now_v7().to_string()))?;
+
+        assert!(req.current_user_id.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn successfully_admit_admin_role() -> Result<(), Error> {
+        let mut interceptor = AdminInterceptor::new(AuthInterceptor::new(&settings())?);
+
+        let admin = role_token(Uuid::now_v7().to_string(), "SECRET", vec!["admin".into()])?;
+        let mut req = Request::new(());
+        req.metadata_mut()
+            .insert("authorization", format!("Bearer {admin}").parse()?);
+        assert!(interceptor.call(req).is_ok());
+
+        let user = token(Uuid::now_v7().to_string(), "SECRET")?;
+        let mut req = Request::new(());
+        req.metadata_mut()
+            .insert("authorization", format!("Bearer {user}").parse()?);
+        assert!(interceptor.call(req).is_err());
+
+        let mut req = Request::new(());
+        req.metadata_mut()
+            .insert("x-internal-key", "INTERNAL".parse()?);
+        assert!(interceptor.call(req).is_err());
+
+        assert!(interceptor.call(Request::new(())).is_err());
+
+        Ok(())
+    }
+}
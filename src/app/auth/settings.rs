@@ -0,0 +1,29 @@
+use jsonwebtoken::Algorithm;
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct AuthSettings {
+    pub algorithm: Algorithm,
+    // секрет для HS* или публичный ключ в PEM для RS*/PS*/ES*/EdDSA, дефолта нет
+    pub key: String,
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
+    // режим совместимости: внутренние сервисы с этим ключом могут передавать current_user_id
+    pub internal_key: Option<String>,
+    // роль в claims.roles, открывающая AdminService
+    pub admin_role: Option<String>,
+}
+
+#[cfg(test)]
+impl AuthSettings {
+    pub fn stub() -> Self {
+        Self {
+            algorithm: Algorithm::HS256,
+            key: "SECRET".into(),
+            issuer: None,
+            audience: None,
+            internal_key: None,
+            admin_role: Some("admin".into()),
+        }
+    }
+}
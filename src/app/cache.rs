@@ -0,0 +1,369 @@
+use std::{
+    collections::HashSet,
+    num::NonZeroUsize,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use bzd_lib::error::Error;
+use bzd_messages_api::events::{Message, Stream, Topic, topic::Type as TopicType};
+use chrono::NaiveDateTime;
+use futures::StreamExt as _;
+use lru::LruCache;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::app::{
+    cache::settings::{CacheSettings, LruSettings},
+    error::AppError,
+    events, grpc,
+    messages::repo::{MessageModel, MessageUserModel, StreamModel},
+    settings::AppSettings,
+    state::AppState,
+    topics::repo::TopicModel,
+};
+
+pub mod settings;
+
+#[derive(Clone)]
+pub struct CacheState {
+    pub messages: Lru<MessageModel>,
+    pub topics: Lru<TopicModel>,
+    // ключ - message_id корневого сообщения, как в get_streams
+    pub streams: Lru<(StreamModel, Vec<MessageUserModel>)>,
+}
+
+impl CacheState {
+    pub fn new(settings: &CacheSettings) -> Self {
+        Self {
+            messages: Lru::new(&settings.messages),
+            topics: Lru::new(&settings.topics),
+            streams: Lru::new(&settings.streams),
+        }
+    }
+}
+
+// Версия значения - его updated_at, по ней отсекаются заполнения старее инвалидации
+pub trait Versioned {
+    fn version(&self) -> NaiveDateTime;
+}
+
+impl Versioned for MessageModel {
+    fn version(&self) -> NaiveDateTime {
+        self.updated_at
+    }
+}
+
+impl Versioned for TopicModel {
+    fn version(&self) -> NaiveDateTime {
+        self.updated_at
+    }
+}
+
+impl Versioned for (StreamModel, Vec<MessageUserModel>) {
+    fn version(&self) -> NaiveDateTime {
+        self.0.updated_at
+    }
+}
+
+struct Entries<V> {
+    values: LruCache<Uuid, (Instant, V)>,
+    // минимальная версия, которую можно класть в кэш после инвалидации
+    floors: LruCache<Uuid, NaiveDateTime>,
+}
+
+#[derive(Clone)]
+pub struct Lru<V> {
+    entries: Arc<Mutex<Entries<V>>>,
+    ttl: Duration,
+}
+
+impl<V: Clone + Versioned> Lru<V> {
+    pub fn new(settings: &LruSettings) -> Self {
+        let capacity = NonZeroUsize::new(settings.capacity).unwrap_or(NonZeroUsize::MIN);
+
+        Self {
+            entries: Arc::new(Mutex::new(Entries {
+                values: LruCache::new(capacity),
+                floors: LruCache::new(capacity),
+            })),
+            ttl: Duration::from_secs(settings.ttl),
+        }
+    }
+
+    // Загрузка с отстающей реплики или начатая до инвалидации может вернуть устаревшее значение:
+    // его отдаем как есть, но в кэш не кладем, пока версия не догонит инвалидацию
+    pub async fn get_or_load<F, Fut>(
+        &self,
+        ids: Vec<Uuid>,
+        id: fn(&V) -> Uuid,
+        load: F,
+    ) -> Result<Vec<V>, AppError>
+    where
+        F: FnOnce(Vec<Uuid>) -> Fut,
+        Fut: Future<Output = Result<Vec<V>, AppError>>,
+    {
+        let mut seen = HashSet::new();
+        let mut found = vec![];
+        let mut missing = vec![];
+
+        {
+            let mut entries = self.lock()?;
+
+            for it in ids.into_iter().filter(|it| seen.insert(*it)) {
+                match entries.values.get(&it) {
+                    Some((at, value)) if at.elapsed() < self.ttl => found.push(value.clone()),
+                    _ => missing.push(it),
+                }
+            }
+        }
+
+        if missing.is_empty() {
+            return Ok(found);
+        }
+
+        let loaded = load(missing).await?;
+
+        let mut entries = self.lock()?;
+        let now = Instant::now();
+
+        for value in loaded {
+            let key = id(&value);
+
+            if entries
+                .floors
+                .peek(&key)
+                .is_none_or(|floor| value.version() >= *floor)
+            {
+                entries.floors.pop(&key);
+                entries.values.put(key, (now, value.clone()));
+            }
+
+            found.push(value);
+        }
+
+        Ok(found)
+    }
+
+    // version - updated_at записи, из-за которой инвалидируем
+    pub fn invalidate(&self, id: Uuid, version: NaiveDateTime) {
+        if let Ok(mut entries) = self.lock() {
+            entries.values.pop(&id);
+
+            let floor = entries
+                .floors
+                .peek(&id)
+                .map_or(version, |it| version.max(*it));
+            entries.floors.put(id, floor);
+        }
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Entries<V>>, AppError> {
+        self.entries.lock().map_err(|_| AppError::Other)
+    }
+}
+
+// Локальные записи инвалидируют кэш сразу, а остальные реплики узнают о них из наших же эвентов.
+// Подписка обычная, не JetStream консьюмер: эвент должна получить каждая реплика
+pub async fn invalidator(state: &AppState, settings: &AppSettings) -> Result<(), Error> {
+    let nats = &state.messages.mess.nats;
+    let messages_subject = settings.messages.events.messages.subject.clone();
+    let streams_subject = settings.messages.events.streams.subject.clone();
+    let topics_subject = settings.topics.events.topics.subject.clone();
+
+    let mut messages = futures::stream::select_all([
+        nats.subscribe(messages_subject.clone()).await?,
+        nats.subscribe(streams_subject.clone()).await?,
+        nats.subscribe(topics_subject.clone()).await?,
+    ]);
+
+    info!("cache: invalidator started");
+
+    loop {
+        let message = tokio::select! {
+            message = messages.next() => message,
+            _ = state.shutdown.wait() => break,
+        };
+
+        let Some(message) = message else {
+            break;
+        };
+
+        let res = if message.subject.as_str() == messages_subject {
+            invalidate_message(&state.cache, &message)
+        } else if message.subject.as_str() == streams_subject {
+            invalidate_stream(&state.cache, &message)
+        } else if message.subject.as_str() == topics_subject {
+            invalidate_topic(&state.cache, &message)
+        } else {
+            Ok(())
+        };
+
+        if let Err(err) = res {
+            error!("cache: {}", err);
+        }
+    }
+
+    info!("cache: invalidator stopped");
+
+    Ok(())
+}
+
+fn invalidate_message(cache: &CacheState, message: &async_nats::Message) -> Result<(), AppError> {
+    let Some(received) = events::receive(message.headers.as_ref(), &message.payload)? else {
+        return Ok(());
+    };
+
+    let message: Message = received.decode()?;
+    cache
+        .messages
+        .invalidate(message.message_id().parse()?, version(message.updated_at)?);
+
+    Ok(())
+}
+
+fn invalidate_stream(cache: &CacheState, message: &async_nats::Message) -> Result<(), AppError> {
+    let Some(received) = events::receive(message.headers.as_ref(), &message.payload)? else {
+        return Ok(());
+    };
+
+    let stream: Stream = received.decode()?;
+    cache
+        .streams
+        .invalidate(stream.message_id().parse()?, version(stream.updated_at)?);
+
+    Ok(())
+}
+
+fn invalidate_topic(cache: &CacheState, message: &async_nats::Message) -> Result<(), AppError> {
+    let Some(received) = events::receive(message.headers.as_ref(), &message.payload)? else {
+        return Ok(());
+    };
+
+    let is_deleted = matches!(received.tp.parse(), Ok(TopicType::Deleted));
+    let topic: Topic = received.decode()?;
+
+    // удаленный топик свежее уже не станет
+    let version = if is_deleted {
+        NaiveDateTime::MAX
+    } else {
+        version(topic.updated_at)?
+    };
+
+    cache.topics.invalidate(topic.topic_id().parse()?, version);
+
+    Ok(())
+}
+
+// в эвентах updated_at без долей секунды, поэтому порог не выше настоящей версии
+fn version(updated_at: Option<prost_types::Timestamp>) -> Result<NaiveDateTime, AppError> {
+    grpc::from_proto_timestamp(updated_at.ok_or(AppError::Validation)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use bzd_lib::error::Error;
+    use chrono::{DateTime, NaiveDateTime, TimeDelta};
+    use uuid::Uuid;
+
+    use crate::app::{
+        cache::{Lru, Versioned, settings::LruSettings},
+        error::AppError,
+    };
+
+    // версия v7 ключа - время его создания
+    impl Versioned for Uuid {
+        fn version(&self) -> NaiveDateTime {
+            self.get_timestamp()
+                .and_then(|it| {
+                    let (seconds, nanos) = it.to_unix();
+                    DateTime::from_timestamp(seconds as i64, nanos)
+                })
+                .map(|it| it.naive_utc())
+                .unwrap_or_default()
+        }
+    }
+
+    fn lru(ttl: u64) -> Lru<Uuid> {
+        Lru::new(&LruSettings { capacity: 2, ttl })
+    }
+
+    async fn get(
+        lru: &Lru<Uuid>,
+        ids: Vec<Uuid>,
+        loads: &AtomicUsize,
+    ) -> Result<Vec<Uuid>, AppError> {
+        lru.get_or_load(
+            ids,
+            |it| *it,
+            |missing| async move {
+                loads.fetch_add(missing.len(), Ordering::Relaxed);
+                Ok(missing)
+            },
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn successfully_hit_cache() -> Result<(), Error> {
+        let lru = lru(60);
+        let loads = AtomicUsize::new(0);
+        let id = Uuid::now_v7();
+
+        get(&lru, vec![id, id], &loads).await?;
+        let res = get(&lru, vec![id], &loads).await?;
+
+        assert_eq!(res, vec![id]);
+        assert_eq!(loads.load(Ordering::Relaxed), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn successfully_invalidate() -> Result<(), Error> {
+        let lru = lru(60);
+        let loads = AtomicUsize::new(0);
+        let id = Uuid::now_v7();
+
+        get(&lru, vec![id], &loads).await?;
+        lru.invalidate(id, id.version());
+        get(&lru, vec![id], &loads).await?;
+        get(&lru, vec![id], &loads).await?;
+
+        assert_eq!(loads.load(Ordering::Relaxed), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn failed_fill_older_than_invalidation() -> Result<(), Error> {
+        let lru = lru(60);
+        let loads = AtomicUsize::new(0);
+        let id = Uuid::now_v7();
+
+        // реплика отдает строку старее записи, из-за которой инвалидировали
+        lru.invalidate(id, id.version() + TimeDelta::seconds(1));
+        get(&lru, vec![id], &loads).await?;
+        get(&lru, vec![id], &loads).await?;
+
+        assert_eq!(loads.load(Ordering::Relaxed), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn successfully_expire() -> Result<(), Error> {
+        let lru = lru(0);
+        let loads = AtomicUsize::new(0);
+        let id = Uuid::now_v7();
+
+        get(&lru, vec![id], &loads).await?;
+        get(&lru, vec![id], &loads).await?;
+
+        assert_eq!(loads.load(Ordering::Relaxed), 2);
+
+        Ok(())
+    }
+}
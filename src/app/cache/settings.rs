@@ -0,0 +1,31 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct CacheSettings {
+    pub messages: LruSettings,
+    pub topics: LruSettings,
+    pub streams: LruSettings,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub struct LruSettings {
+    pub capacity: usize,
+    // секунды, страховка на случай потерянной инвалидации
+    pub ttl: u64,
+}
+
+#[cfg(test)]
+impl CacheSettings {
+    pub fn stub() -> Self {
+        let lru = LruSettings {
+            capacity: 2,
+            ttl: 1,
+        };
+
+        Self {
+            messages: lru,
+            topics: lru,
+            streams: lru,
+        }
+    }
+}
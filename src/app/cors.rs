@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use axum::http::{HeaderName, HeaderValue, Method, header};
+use bzd_lib::error::Error;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::app::cors::settings::CorsSettings;
+
+pub mod settings;
+
+pub fn layer(settings: &CorsSettings) -> Result<CorsLayer, Error> {
+    let origins = if settings.origins.iter().any(|it| it == "*") {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(
+            settings
+                .origins
+                .iter()
+                .map(|it| it.parse::<HeaderValue>())
+                .collect::<Result<Vec<_>, _>>()?,
+        )
+    };
+
+    let layer = CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::OPTIONS])
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            HeaderName::from_static("x-grpc-web"),
+            HeaderName::from_static("x-user-agent"),
+            HeaderName::from_static("grpc-timeout"),
+            HeaderName::from_static("traceparent"),
+        ])
+        .expose_headers([
+            HeaderName::from_static("grpc-status"),
+            HeaderName::from_static("grpc-message"),
+            HeaderName::from_static("grpc-status-details-bin"),
+            header::RETRY_AFTER,
+        ])
+        .max_age(Duration::from_secs(settings.max_age));
+
+    Ok(layer)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        http::{Method, Request, header},
+        routing::get,
+    };
+    use bzd_lib::error::Error;
+    use tower::ServiceExt as _;
+
+    use crate::app::cors::{self, settings::CorsSettings};
+
+    fn settings(origins: &[&str]) -> CorsSettings {
+        CorsSettings {
+            origins: origins.iter().map(|it| it.to_string()).collect(),
+            max_age: 60,
+        }
+    }
+
+    async fn allowed_origin(
+        settings: &CorsSettings,
+        origin: &str,
+    ) -> Result<Option<String>, Error> {
+        let router = Router::new()
+            .route("/", get(|| async {}))
+            .layer(cors::layer(settings)?);
+
+        let req = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::empty())?;
+
+        let res = router.oneshot(req).await?;
+
+        Ok(res
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .and_then(|it| it.to_str().ok())
+            .map(Into::into))
+    }
+
+    #[tokio::test]
+    async fn successfully_allow_listed_origin() -> Result<(), Error> {
+        let settings = settings(&["http://localhost:5173", "https://bezdna.app"]);
+
+        assert_eq!(
+            allowed_origin(&settings, "https://bezdna.app").await?,
+            Some("https://bezdna.app".into())
+        );
+        assert_eq!(allowed_origin(&settings, "https://evil.app").await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn successfully_allow_any_origin() -> Result<(), Error> {
+        let settings = settings(&["*"]);
+
+        assert_eq!(
+            allowed_origin(&settings, "https://evil.app").await?,
+            Some("*".into())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn failed_invalid_origin() {
+        assert!(cors::layer(&settings(&["http://bad\norigin"])).is_err());
+    }
+}
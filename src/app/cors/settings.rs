@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct CorsSettings {
+    // "*" разрешает любой origin
+    pub origins: Vec<String>,
+    // секунды, сколько браузер кэширует preflight
+    pub max_age: u64,
+}
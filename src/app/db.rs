@@ -1,18 +1,129 @@
-use std::sync::Arc;
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
-use bzd_lib::{error::Error, settings::DBSettings};
+use bzd_lib::error::Error;
 use sea_orm::{ConnectOptions, Database, DbConn};
 
+use crate::app::{
+    db::settings::{DbSettings, PoolSettings},
+    metrics,
+};
+
+pub mod migrate;
+pub mod settings;
+
 #[derive(Clone)]
 pub struct DbState {
     pub conn: Arc<DbConn>,
+    pub replica: Option<Arc<DbConn>>,
+    // флаг ставит health checker, упавшая реплика не должна ронять чтение
+    replica_available: Arc<AtomicBool>,
 }
 
 impl DbState {
-    pub async fn new(settings: &DBSettings) -> Result<Self, Error> {
-        let opt = ConnectOptions::new(&settings.endpoint);
-        let conn = Arc::new(Database::connect(opt).await?);
+    pub async fn new(settings: &DbSettings) -> Result<Self, Error> {
+        let conn = connect(&settings.base.endpoint, &settings.pool).await?;
+
+        if settings.auto_migrate {
+            migrate::up(&conn).await?;
+        }
+
+        let conn = Arc::new(conn);
+
+        let replica = match &settings.read_endpoint {
+            Some(endpoint) => Some(Arc::new(connect(endpoint, &settings.pool).await?)),
+            None => None,
+        };
+
+        Ok(Self {
+            conn,
+            replica,
+            replica_available: Arc::new(AtomicBool::new(true)),
+        })
+    }
+
+    pub fn read_conn(&self) -> &Arc<DbConn> {
+        match &self.replica {
+            Some(replica) if self.replica_available.load(Ordering::Relaxed) => replica,
+            _ => &self.conn,
+        }
+    }
+
+    pub fn set_replica_available(&self, available: bool) {
+        self.replica_available.store(available, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+impl DbState {
+    pub fn stub(conn: DbConn) -> Self {
+        Self {
+            conn: Arc::new(conn),
+            replica: None,
+            replica_available: Arc::new(AtomicBool::new(true)),
+        }
+    }
+}
+
+async fn connect(endpoint: &str, settings: &PoolSettings) -> Result<DbConn, Error> {
+    let mut opt = ConnectOptions::new(endpoint);
+    opt.sqlx_logging(settings.logging);
+
+    if let Some(max_connections) = settings.max_connections {
+        opt.max_connections(max_connections);
+    }
+
+    if let Some(min_connections) = settings.min_connections {
+        opt.min_connections(min_connections);
+    }
+
+    if let Some(connect_timeout) = settings.connect_timeout {
+        opt.connect_timeout(Duration::from_secs(connect_timeout));
+    }
+
+    if let Some(acquire_timeout) = settings.acquire_timeout {
+        opt.acquire_timeout(Duration::from_secs(acquire_timeout));
+    }
+
+    if let Some(idle_timeout) = settings.idle_timeout {
+        opt.idle_timeout(Duration::from_secs(idle_timeout));
+    }
+
+    if let Some(statement_timeout) = settings.statement_timeout {
+        opt.map_sqlx_postgres_opts(move |it| {
+            it.options([("statement_timeout", statement_timeout.to_string())])
+        });
+    }
+
+    let mut conn = Database::connect(opt).await?;
+    conn.set_metric_callback(metrics::db_query);
+
+    Ok(conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    use crate::app::db::DbState;
+
+    #[test]
+    fn successfully_fall_back_to_primary() {
+        let mut db = DbState::stub(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let replica = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        db.replica = Some(replica.clone());
+
+        assert!(Arc::ptr_eq(db.read_conn(), &replica));
+
+        db.set_replica_available(false);
 
-        Ok(Self { conn })
+        assert!(Arc::ptr_eq(db.read_conn(), &db.conn));
     }
 }
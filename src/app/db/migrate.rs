@@ -0,0 +1,105 @@
+use bzd_lib::error::Error;
+use bzd_messages_migration::{Migrator, MigratorTrait as _};
+use sea_orm::{ConnectionTrait as _, DbBackend, DbConn, Statement, TransactionTrait as _};
+use tracing::info;
+
+use crate::app::error::AppError;
+
+// любое фиксированное число, главное чтобы не пересекалось с другими локами в этой базе
+const LOCK_KEY: i64 = 0x0062_7a64_5f6d_7367;
+
+pub enum Command {
+    Up(Option<u32>),
+    Down(Option<u32>),
+    Status,
+}
+
+impl TryFrom<&[String]> for Command {
+    type Error = AppError;
+
+    fn try_from(args: &[String]) -> Result<Self, Self::Error> {
+        let steps = args
+            .get(1)
+            .map(|it| it.parse::<u32>().map_err(|_| AppError::Validation))
+            .transpose()?;
+
+        match args.first().map(String::as_str) {
+            Some("up") | None => Ok(Self::Up(steps)),
+            Some("down") => Ok(Self::Down(steps.or(Some(1)))),
+            Some("status") => Ok(Self::Status),
+            Some(_) => Err(AppError::Validation),
+        }
+    }
+}
+
+pub async fn run(db: &DbConn, command: Command) -> Result<(), Error> {
+    match command {
+        Command::Up(steps) => locked(db, Direction::Up(steps)).await?,
+        Command::Down(steps) => locked(db, Direction::Down(steps)).await?,
+        Command::Status => {
+            for migration in Migrator::get_migration_with_status(db).await? {
+                info!("migrate: {} {}", migration.name(), migration.status());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn up(db: &DbConn) -> Result<(), Error> {
+    locked(db, Direction::Up(None)).await
+}
+
+enum Direction {
+    Up(Option<u32>),
+    Down(Option<u32>),
+}
+
+// Postgres умеет DDL в транзакции, поэтому все миграции идут одной транзакцией под xact локом,
+// лок отпустится на коммите или откате, даже если процесс упадет
+async fn locked(db: &DbConn, direction: Direction) -> Result<(), Error> {
+    let tx = db.begin().await?;
+
+    tx.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT pg_advisory_xact_lock($1)",
+        [LOCK_KEY.into()],
+    ))
+    .await?;
+
+    match direction {
+        Direction::Up(steps) => Migrator::up(&tx, steps).await?,
+        Direction::Down(steps) => Migrator::down(&tx, steps).await?,
+    }
+
+    tx.commit().await?;
+
+    info!("migrate: done");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::{db::migrate::Command, error::AppError};
+
+    fn parse(args: &[&str]) -> Result<Command, AppError> {
+        let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+
+        Command::try_from(args.as_slice())
+    }
+
+    #[test]
+    fn successfully_parse_command() {
+        assert!(matches!(parse(&["down", "2"]), Ok(Command::Down(Some(2)))));
+        assert!(matches!(parse(&["down"]), Ok(Command::Down(Some(1)))));
+        assert!(matches!(parse(&[]), Ok(Command::Up(None))));
+        assert!(matches!(parse(&["status"]), Ok(Command::Status)));
+    }
+
+    #[test]
+    fn failed_parse_command() {
+        assert!(parse(&["sideways"]).is_err());
+        assert!(parse(&["up", "x"]).is_err());
+    }
+}
@@ -0,0 +1,28 @@
+use bzd_lib::settings::DBSettings;
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct DbSettings {
+    #[serde(flatten)]
+    pub base: DBSettings,
+    // накатывать миграции при старте, реплики ждут друг друга на advisory lock
+    #[serde(default)]
+    pub auto_migrate: bool,
+    #[serde(default)]
+    pub pool: PoolSettings,
+    // реплика для читающих ручек, без нее все идет в primary
+    pub read_endpoint: Option<String>,
+}
+
+// таймауты в секундах, statement_timeout в миллисекундах, пустое значение оставляет дефолт sqlx
+#[derive(Deserialize, Clone, Default)]
+pub struct PoolSettings {
+    pub max_connections: Option<u32>,
+    pub min_connections: Option<u32>,
+    pub connect_timeout: Option<u64>,
+    pub acquire_timeout: Option<u64>,
+    pub idle_timeout: Option<u64>,
+    pub statement_timeout: Option<u64>,
+    #[serde(default)]
+    pub logging: bool,
+}
@@ -1,11 +1,35 @@
+use std::time::Duration;
+
+use axum::{
+    Json,
+    http::{HeaderValue, StatusCode, header::RETRY_AFTER},
+    response::{IntoResponse, Response},
+};
+use sea_orm::SqlErr;
+use serde::Serialize;
 use thiserror::Error;
-use tonic::Status;
+use tonic::{Code, Status};
+use tonic_types::{ErrorDetails, FieldViolation, StatusExt as _};
 
 impl From<AppError> for Status {
     fn from(error: AppError) -> Self {
-        match error {
-            AppError::Validation => Self::invalid_argument(error.to_string()),
-            // AppError::NotFound => Self::not_found(error.to_string()),
+        match &error {
+            AppError::Validation | AppError::Uuid(_) => Self::invalid_argument(error.to_string()),
+            AppError::Fields(errors) => Self::with_error_details(
+                Code::InvalidArgument,
+                error.to_string(),
+                ErrorDetails::with_bad_request(field_violations(errors)),
+            ),
+            AppError::Unauthenticated => Self::unauthenticated(error.to_string()),
+            AppError::NotFound => Self::not_found(error.to_string()),
+            AppError::Forbidden => Self::permission_denied(error.to_string()),
+            AppError::Conflict => Self::already_exists(error.to_string()),
+            AppError::RateLimited(retry_after) => rate_limited(&error, *retry_after),
+            AppError::Db(err)
+                if matches!(err.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) =>
+            {
+                Self::already_exists(AppError::Conflict.to_string())
+            }
             _ => Self::internal(error.to_string()),
         }
     }
@@ -16,19 +40,31 @@ pub enum AppError {
     // Ok
     #[error("VALIDATION")]
     Validation,
+    #[error("VALIDATION")]
+    Fields(validator::ValidationErrors),
 
     #[error("ENCODE")]
     Encode(#[from] prost::EncodeError),
+    #[error("DECODE")]
+    Decode(#[from] prost::DecodeError),
+    #[error("JSON")]
+    Json(#[from] serde_json::Error),
     #[error("PUBLISH")]
     Publish(#[from] async_nats::jetstream::context::PublishError),
     #[error("DB")]
     Db(#[from] sea_orm::DbErr),
     #[error("UUID")]
     Uuid(#[from] uuid::Error),
+    #[error("UNAUTHENTICATED")]
+    Unauthenticated,
     #[error("NOT_FOUND")]
     NotFound,
     #[error("FORBIDDEN")]
     Forbidden,
+    #[error("CONFLICT")]
+    Conflict,
+    #[error("RATE_LIMITED")]
+    RateLimited(Duration),
     #[error("OTHER")]
     Other,
 
@@ -37,8 +73,171 @@ pub enum AppError {
     Unreachable,
 }
 
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fields: Vec<FieldBody>,
+}
+
+#[derive(Serialize)]
+struct FieldBody {
+    field: String,
+    description: String,
+}
+
+// те же коды, что и в gRPC маппинге выше, только в http терминах
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let code = match &self {
+            AppError::Validation | AppError::Fields(_) | AppError::Uuid(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            AppError::Unauthenticated => StatusCode::UNAUTHORIZED,
+            AppError::NotFound => StatusCode::NOT_FOUND,
+            AppError::Forbidden => StatusCode::FORBIDDEN,
+            AppError::Conflict => StatusCode::CONFLICT,
+            AppError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Db(err)
+                if matches!(err.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) =>
+            {
+                StatusCode::CONFLICT
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        let fields = match &self {
+            AppError::Fields(errors) => field_violations(errors)
+                .into_iter()
+                .map(|it| FieldBody {
+                    field: it.field,
+                    description: it.description,
+                })
+                .collect(),
+            _ => vec![],
+        };
+
+        let error = match code {
+            StatusCode::CONFLICT => AppError::Conflict.to_string(),
+            _ => self.to_string(),
+        };
+
+        let mut res = (code, Json(ErrorBody { error, fields })).into_response();
+
+        if let AppError::RateLimited(retry_after) = self {
+            res.headers_mut().insert(
+                RETRY_AFTER,
+                HeaderValue::from(retry_after_secs(retry_after)),
+            );
+        }
+
+        res
+    }
+}
+
 impl From<validator::ValidationErrors> for AppError {
-    fn from(_: validator::ValidationErrors) -> Self {
-        Self::Validation
+    fn from(errors: validator::ValidationErrors) -> Self {
+        Self::Fields(errors)
+    }
+}
+
+fn rate_limited(error: &AppError, retry_after: Duration) -> Status {
+    let mut status = Status::with_error_details(
+        Code::ResourceExhausted,
+        error.to_string(),
+        ErrorDetails::with_retry_info(Some(retry_after)),
+    );
+
+    status
+        .metadata_mut()
+        .insert("retry-after", retry_after_secs(retry_after).into());
+
+    status
+}
+
+fn retry_after_secs(retry_after: Duration) -> u64 {
+    retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0)
+}
+
+fn field_violations(errors: &validator::ValidationErrors) -> Vec<FieldViolation> {
+    errors
+        .field_errors()
+        .into_iter()
+        .flat_map(|(field, errors)| {
+            errors.iter().map(move |error| {
+                FieldViolation::new(
+                    field.to_string(),
+                    error
+                        .message
+                        .as_deref()
+                        .unwrap_or(error.code.as_ref())
+                        .to_string(),
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::{http::StatusCode, response::IntoResponse as _};
+    use tonic::{Code, Status};
+    use tonic_types::StatusExt as _;
+    use validator::Validate;
+
+    use crate::app::error::AppError;
+
+    #[derive(Validate)]
+    struct Data {
+        #[validate(length(min = 2))]
+        text: String,
+    }
+
+    #[test]
+    fn successfully_map_not_found() {
+        let status = Status::from(AppError::NotFound);
+
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
+    #[test]
+    fn successfully_map_rate_limited() {
+        let status = Status::from(AppError::RateLimited(Duration::from_millis(1500)));
+
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(
+            status
+                .metadata()
+                .get("retry-after")
+                .and_then(|it| it.to_str().ok()),
+            Some("2")
+        );
+    }
+
+    #[test]
+    fn successfully_map_http_not_found() {
+        let res = AppError::NotFound.into_response();
+
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn successfully_map_field_violations() {
+        let data = Data { text: "A".into() };
+        let error: AppError = data.validate().unwrap_err().into();
+
+        let status = Status::from(error);
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        let bad_request = status.get_details_bad_request();
+        assert!(bad_request.is_some());
+
+        let violations = bad_request
+            .map(|it| it.field_violations)
+            .unwrap_or_default();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].field, "text");
     }
 }
@@ -0,0 +1,372 @@
+use async_nats::{
+    HeaderMap,
+    header::{HeaderName, NATS_MESSAGE_ID},
+    jetstream::Context,
+};
+use bytes::{Bytes, BytesMut};
+use chrono::{NaiveDateTime, Utc};
+use sea_orm::{ConnectionTrait, DbConn, TransactionTrait as _};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use uuid::Uuid;
+
+use crate::app::{
+    error::AppError,
+    events::{repo::EventOutboxModel, settings::EventsMode},
+    metrics, telemetry,
+};
+
+mod repo;
+pub mod settings;
+
+const SOURCE: &str = concat!("/", env!("CARGO_PKG_NAME"));
+const SPEC_VERSION: &str = "1.0";
+const PROTOBUF: &str = "application/protobuf";
+const JSON: &str = "application/json";
+const STRUCTURED: &str = "application/cloudevents+json";
+
+pub struct Event<M> {
+    pub tp: String,
+    pub subject: Uuid,
+    // updated_at сущности, вместе с типом и subject даёт стабильный ce_id
+    pub version: NaiveDateTime,
+    pub data: M,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Structured<M> {
+    specversion: String,
+    id: String,
+    source: String,
+    #[serde(rename = "type")]
+    tp: String,
+    subject: String,
+    time: String,
+    datacontenttype: String,
+    data: M,
+}
+
+// Закодированный эвент вместе с Nats-Msg-Id, его можно отправить сразу или отложить в outbox
+pub struct Outgoing {
+    pub subject: String,
+    pub headers: HeaderMap,
+    pub payload: Bytes,
+}
+
+pub fn prepare<M: prost::Message + Serialize>(
+    subject: &str,
+    mode: EventsMode,
+    event: Event<M>,
+) -> Result<Outgoing, AppError> {
+    let (headers, payload) = encode(mode, event)?;
+
+    Ok(Outgoing {
+        subject: subject.into(),
+        headers,
+        payload,
+    })
+}
+
+pub async fn publish(js: &Context, outgoing: Outgoing) -> Result<(), AppError> {
+    let Outgoing {
+        subject,
+        headers,
+        payload,
+    } = outgoing;
+
+    // отказ сервера (нет стрима на subject) и дубль по Nats-Msg-Id приходят только в аке,
+    // поэтому результат пишем после него
+    let res = match js
+        .publish_with_headers(subject.clone(), headers, payload)
+        .await
+    {
+        Ok(ack) => ack.await,
+        Err(err) => Err(err),
+    };
+
+    let result = match &res {
+        Ok(ack) if ack.duplicate => "duplicate",
+        Ok(_) => "ok",
+        Err(_) => "error",
+    };
+    metrics::publish(&subject, result);
+
+    res?;
+
+    Ok(())
+}
+
+// Эвент пишется в той же транзакции, что и изменение, и уходит в JetStream уже после коммита
+pub async fn enqueue<T: ConnectionTrait>(db: &T, outgoing: Outgoing) -> Result<(), AppError> {
+    repo::create_event_outbox(db, outgoing.try_into()?).await
+}
+
+// Строки берутся под SKIP LOCKED, так реплики не отправляют одно и то же. Если отправка
+// упала посреди пачки, откат вернет уже отправленные, повтор JetStream отбросит по Nats-Msg-Id
+pub async fn relay(db: &DbConn, js: &Context, limit: u64) -> Result<(), AppError> {
+    loop {
+        let tx = db.begin().await?;
+
+        let events_outbox = repo::lock_events_outbox(&tx, limit).await?;
+        let count = events_outbox.len() as u64;
+
+        for event_outbox in events_outbox {
+            let event_outbox_id = event_outbox.event_outbox_id;
+
+            publish(js, event_outbox.try_into()?).await?;
+            repo::delete_event_outbox(&tx, event_outbox_id).await?;
+        }
+
+        tx.commit().await?;
+
+        if count < limit {
+            return Ok(());
+        }
+    }
+}
+
+impl TryFrom<Outgoing> for EventOutboxModel {
+    type Error = AppError;
+
+    fn try_from(outgoing: Outgoing) -> Result<Self, Self::Error> {
+        let headers: Vec<(String, String)> = outgoing
+            .headers
+            .iter()
+            .flat_map(|(name, values)| {
+                values
+                    .iter()
+                    .map(move |value| (name.to_string(), value.as_str().to_string()))
+            })
+            .collect();
+
+        Ok(Self::new(
+            outgoing.subject,
+            serde_json::to_value(headers)?,
+            outgoing.payload.to_vec(),
+        ))
+    }
+}
+
+impl TryFrom<EventOutboxModel> for Outgoing {
+    type Error = AppError;
+
+    fn try_from(event_outbox: EventOutboxModel) -> Result<Self, Self::Error> {
+        let pairs: Vec<(String, String)> = serde_json::from_value(event_outbox.headers)?;
+
+        // имена парсим, чтобы Nats-Msg-Id и другие стандартные заголовки восстановились как есть
+        let mut headers = HeaderMap::new();
+        for (name, value) in &pairs {
+            let name: HeaderName = name.parse().map_err(|_| AppError::Other)?;
+            headers.append(name, value.as_str());
+        }
+
+        Ok(Self {
+            subject: event_outbox.subject,
+            headers,
+            payload: event_outbox.payload.into(),
+        })
+    }
+}
+
+fn encode<M: prost::Message + Serialize>(
+    mode: EventsMode,
+    event: Event<M>,
+) -> Result<(HeaderMap, Bytes), AppError> {
+    // повторная публикация того же изменения отбрасывается JetStream по Nats-Msg-Id
+    let id = format!(
+        "{}:{}:{}",
+        event.tp,
+        event.subject,
+        event.version.and_utc().timestamp_micros()
+    );
+    let time = Utc::now().to_rfc3339();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(NATS_MESSAGE_ID, id.as_str());
+    telemetry::inject(&mut headers);
+
+    let payload = match mode {
+        EventsMode::Binary => {
+            headers.insert("ce_id", id.as_str());
+            headers.insert("ce_source", SOURCE);
+            headers.insert("ce_specversion", SPEC_VERSION);
+            headers.insert("ce_type", event.tp.as_str());
+            headers.insert("ce_time", time.as_str());
+            headers.insert("ce_subject", event.subject.to_string());
+            headers.insert("content-type", PROTOBUF);
+
+            let mut buf = BytesMut::new();
+            event.data.encode(&mut buf)?;
+
+            buf.freeze()
+        }
+        EventsMode::Structured => {
+            headers.insert("content-type", STRUCTURED);
+
+            let payload = serde_json::to_vec(&Structured {
+                specversion: SPEC_VERSION.into(),
+                id,
+                source: SOURCE.into(),
+                tp: event.tp,
+                subject: event.subject.to_string(),
+                time,
+                datacontenttype: JSON.into(),
+                data: event.data,
+            })?;
+
+            payload.into()
+        }
+    };
+
+    Ok((headers, payload))
+}
+
+pub struct Received<'a> {
+    pub tp: String,
+    data: Data<'a>,
+}
+
+enum Data<'a> {
+    Protobuf(&'a [u8]),
+    Json(serde_json::Value),
+}
+
+pub fn receive<'a>(
+    headers: Option<&HeaderMap>,
+    payload: &'a [u8],
+) -> Result<Option<Received<'a>>, AppError> {
+    let content_type = headers
+        .and_then(|it| it.get("content-type"))
+        .map(|it| it.as_str());
+
+    if content_type == Some(STRUCTURED) {
+        let event: Structured<serde_json::Value> = serde_json::from_slice(payload)?;
+
+        return Ok(Some(Received {
+            tp: event.tp,
+            data: Data::Json(event.data),
+        }));
+    }
+
+    let Some(tp) = headers.and_then(|it| it.get("ce_type")) else {
+        return Ok(None);
+    };
+
+    Ok(Some(Received {
+        tp: tp.as_str().into(),
+        data: Data::Protobuf(payload),
+    }))
+}
+
+impl Received<'_> {
+    pub fn decode<M: prost::Message + Default + DeserializeOwned>(self) -> Result<M, AppError> {
+        match self.data {
+            Data::Protobuf(payload) => Ok(M::decode(payload)?),
+            Data::Json(value) => Ok(serde_json::from_value(value)?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_nats::header::NATS_MESSAGE_ID;
+    use bzd_lib::error::Error;
+    use bzd_messages_api::events::{MessageTopic, message_topic::Type};
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use crate::app::{
+        error::AppError,
+        events::{
+            Event, Outgoing, encode, prepare, receive, repo::EventOutboxModel, settings::EventsMode,
+        },
+    };
+
+    fn event() -> Event<MessageTopic> {
+        let message_topic_id = Uuid::now_v7();
+
+        Event {
+            tp: Type::Created.to_string(),
+            subject: message_topic_id,
+            version: Utc::now().naive_utc(),
+            data: MessageTopic {
+                message_topic_id: Some(message_topic_id.into()),
+                message_id: Some(Uuid::now_v7().into()),
+                topic_id: Some(Uuid::now_v7().into()),
+                created_at: None,
+                updated_at: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_ok_binary_roundtrip() -> Result<(), Error> {
+        let event = event();
+        let data = event.data.clone();
+
+        let (headers, payload) = encode(EventsMode::Binary, event)?;
+
+        assert_eq!(
+            headers.get("ce_id").map(|it| it.as_str()),
+            headers.get(NATS_MESSAGE_ID).map(|it| it.as_str())
+        );
+        assert_eq!(
+            headers.get("ce_subject").map(|it| it.as_str()),
+            data.message_topic_id.as_deref()
+        );
+
+        let received = receive(Some(&headers), &payload)?.ok_or(AppError::Unreachable)?;
+
+        assert_eq!(received.tp, Type::Created.to_string());
+        assert_eq!(received.decode::<MessageTopic>()?, data);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ok_structured_roundtrip() -> Result<(), Error> {
+        let event = event();
+        let data = event.data.clone();
+
+        let (headers, payload) = encode(EventsMode::Structured, event)?;
+
+        assert!(headers.get("ce_type").is_none());
+
+        let received = receive(Some(&headers), &payload)?.ok_or(AppError::Unreachable)?;
+
+        assert_eq!(received.tp, Type::Created.to_string());
+        assert_eq!(received.decode::<MessageTopic>()?, data);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ok_outbox_roundtrip() -> Result<(), Error> {
+        let event = event();
+        let data = event.data.clone();
+
+        let outgoing = prepare("bzd.test", EventsMode::Binary, event)?;
+        let id = outgoing
+            .headers
+            .get(NATS_MESSAGE_ID)
+            .map(|it| it.as_str().to_string());
+
+        let event_outbox = EventOutboxModel::try_from(outgoing)?;
+        let outgoing = Outgoing::try_from(event_outbox)?;
+
+        assert_eq!(outgoing.subject, "bzd.test");
+        assert_eq!(
+            outgoing
+                .headers
+                .get(NATS_MESSAGE_ID)
+                .map(|it| it.as_str().to_string()),
+            id
+        );
+
+        let received =
+            receive(Some(&outgoing.headers), &outgoing.payload)?.ok_or(AppError::Unreachable)?;
+
+        assert_eq!(received.decode::<MessageTopic>()?, data);
+
+        Ok(())
+    }
+}
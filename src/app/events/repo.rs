@@ -0,0 +1,51 @@
+use sea_orm::{
+    ConnectionTrait, EntityTrait as _, IntoActiveModel as _, QueryOrder as _, QuerySelect as _,
+    sea_query::{LockBehavior, LockType},
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app::error::AppError;
+
+pub mod event_outbox;
+
+pub type EventOutboxModel = event_outbox::Model;
+
+#[instrument(skip_all)]
+pub async fn create_event_outbox<T: ConnectionTrait>(
+    db: &T,
+    model: EventOutboxModel,
+) -> Result<(), AppError> {
+    event_outbox::Entity::insert(model.into_active_model())
+        .exec_without_returning(db)
+        .await?;
+
+    Ok(())
+}
+
+#[instrument(skip_all)]
+pub async fn lock_events_outbox<T: ConnectionTrait>(
+    db: &T,
+    limit: u64,
+) -> Result<Vec<EventOutboxModel>, AppError> {
+    let events_outbox = event_outbox::Entity::find()
+        .order_by_asc(event_outbox::Column::EventOutboxId)
+        .limit(limit)
+        .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+        .all(db)
+        .await?;
+
+    Ok(events_outbox)
+}
+
+#[instrument(skip_all)]
+pub async fn delete_event_outbox<T: ConnectionTrait>(
+    db: &T,
+    event_outbox_id: Uuid,
+) -> Result<(), AppError> {
+    event_outbox::Entity::delete_by_id(event_outbox_id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use chrono::Utc;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "events_outbox")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub event_outbox_id: Uuid,
+    pub subject: String,
+    // пары имя-значение, у NATS заголовок может повторяться
+    pub headers: Json,
+    pub payload: Vec<u8>,
+    pub created_at: DateTime,
+}
+
+impl Model {
+    pub fn new(subject: String, headers: Json, payload: Vec<u8>) -> Self {
+        Self {
+            event_outbox_id: Uuid::now_v7(),
+            subject,
+            headers,
+            payload,
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventsMode {
+    // атрибуты CloudEvents в заголовках, тело protobuf
+    #[default]
+    Binary,
+    // весь конверт в JSON теле, для консьюмеров без protobuf
+    Structured,
+}
@@ -1,6 +1,9 @@
-use chrono::NaiveDateTime;
+use axum::http::{HeaderMap, header::CONTENT_TYPE};
+use chrono::{DateTime, NaiveDateTime};
 use prost_types::Timestamp;
 
+use crate::app::error::AppError;
+
 pub trait ToProtoTimestamp {
     fn to_option_proto(self) -> Option<Timestamp>;
 }
@@ -13,3 +16,16 @@ impl ToProtoTimestamp for NaiveDateTime {
         })
     }
 }
+
+pub fn from_proto_timestamp(timestamp: Timestamp) -> Result<NaiveDateTime, AppError> {
+    DateTime::from_timestamp(timestamp.seconds, timestamp.nanos.max(0) as u32)
+        .map(|it| it.naive_utc())
+        .ok_or(AppError::Validation)
+}
+
+pub fn is_grpc(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|it| it.to_str().ok())
+        .is_some_and(|it| it.starts_with("application/grpc"))
+}
@@ -0,0 +1,114 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use async_nats::connection::State as NatsState;
+use axum::{extract::State, http::StatusCode};
+use bzd_lib::error::Error;
+use bzd_messages_api::{
+    admin::admin_service_server, messages::messages_service_server, topics::topics_service_server,
+    users::users_service_server,
+};
+use sea_orm::DbConn;
+use tonic_health::{ServingStatus, server::HealthReporter};
+use tracing::warn;
+
+use crate::app::{health::settings::HealthSettings, state::AppState};
+
+pub mod settings;
+
+#[derive(Clone, Default)]
+pub struct HealthState {
+    ready: Arc<AtomicBool>,
+}
+
+pub async fn checker(
+    state: &AppState,
+    reporter: HealthReporter,
+    settings: &HealthSettings,
+) -> Result<(), Error> {
+    let interval = Duration::from_secs(settings.interval);
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {},
+            _ = state.shutdown.stopping() => break,
+        }
+
+        let db_state = &state.users.db;
+        let db = ping(&db_state.conn, interval).await;
+        let nats = state.messages.mess.nats.connection_state() == NatsState::Connected;
+
+        if !db {
+            warn!("health: db is unavailable");
+        }
+
+        // реплика на готовность не влияет: пока она лежит, чтение идет в основную базу
+        if let Some(replica) = &db_state.replica {
+            let replica = ping(replica, interval).await;
+
+            if !replica {
+                warn!("health: read replica is unavailable, reading from primary");
+            }
+
+            db_state.set_replica_available(replica);
+        }
+
+        if !nats {
+            warn!("health: nats is unavailable");
+        }
+
+        report(state, &reporter, db, nats).await;
+    }
+
+    // балансировщик должен перестать слать трафик раньше, чем закроется листенер
+    report(state, &reporter, false, false).await;
+
+    Ok(())
+}
+
+async fn ping(conn: &DbConn, timeout: Duration) -> bool {
+    tokio::time::timeout(timeout, conn.ping())
+        .await
+        .is_ok_and(|it| it.is_ok())
+}
+
+async fn report(state: &AppState, reporter: &HealthReporter, db: bool, nats: bool) {
+    // users ходит только в базу, остальным еще нужна публикация эвентов
+    let statuses = [
+        ("", db && nats),
+        (messages_service_server::SERVICE_NAME, db && nats),
+        (topics_service_server::SERVICE_NAME, db && nats),
+        (users_service_server::SERVICE_NAME, db),
+        (admin_service_server::SERVICE_NAME, db && nats),
+    ];
+
+    for (service_name, serving) in statuses {
+        let status = if serving {
+            ServingStatus::Serving
+        } else {
+            ServingStatus::NotServing
+        };
+
+        reporter.set_service_status(service_name, status).await;
+    }
+
+    state.health.ready.store(db && nats, Ordering::Relaxed);
+}
+
+pub async fn livez() -> StatusCode {
+    StatusCode::OK
+}
+
+pub async fn readyz(State(state): State<AppState>) -> StatusCode {
+    if state.health.ready.load(Ordering::Relaxed) {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
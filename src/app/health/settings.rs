@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct HealthSettings {
+    // секунды между проверками, он же таймаут на одну проверку
+    pub interval: u64,
+}
@@ -0,0 +1,203 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bzd_lib::error::Error;
+use chrono::{DateTime, TimeDelta, Utc};
+use tracing::{error, info};
+
+use crate::app::{
+    current_user::CurrentUser,
+    db::DbState,
+    error::AppError,
+    limiter::settings::{LimiterBackend, LimiterSettings, RateLimitSettings},
+    state::AppState,
+};
+
+mod repo;
+pub mod settings;
+
+const MEMORY_PRUNE_SIZE: usize = 100_000;
+
+// Token bucket в виде GCRA: на ключ храним только tat (theoretical arrival time),
+// так его легко держать и в памяти, и одной строкой в Postgres, когда реплик несколько
+#[derive(Clone)]
+pub enum Limiter {
+    Memory(Arc<Mutex<HashMap<String, DateTime<Utc>>>>),
+    Db(DbState),
+}
+
+// варианты называются как сами RPC и через Debug попадают в ключ
+#[allow(clippy::enum_variant_names)]
+#[derive(Clone, Copy, Debug)]
+pub enum Rpc {
+    CreateMessage,
+    CreateMessageTopic,
+    CreateTopicUser,
+}
+
+impl Limiter {
+    pub fn new(settings: &LimiterSettings, db: &DbState) -> Self {
+        match settings.backend {
+            LimiterBackend::Memory => Self::Memory(Arc::default()),
+            LimiterBackend::Db => Self::Db(db.clone()),
+        }
+    }
+
+    pub async fn check(
+        &self,
+        rpc: Rpc,
+        current_user: &Option<CurrentUser>,
+        settings: &RateLimitSettings,
+    ) -> Result<(), AppError> {
+        // анонимов дальше не пустит сервис, считать их не нужно
+        let Some(current_user) = current_user else {
+            return Ok(());
+        };
+
+        let key = format!("{rpc:?}:{}", current_user.user_id);
+        let rate = Rate::from(settings);
+
+        let retry_after = match self {
+            Self::Memory(tats) => {
+                let now = Utc::now();
+                let mut tats = tats.lock().map_err(|_| AppError::Other)?;
+
+                if tats.len() > MEMORY_PRUNE_SIZE {
+                    tats.retain(|_, tat| *tat > now);
+                }
+
+                match rate.take(tats.get(&key).copied(), now) {
+                    Ok(tat) => {
+                        tats.insert(key, tat);
+                        None
+                    }
+                    Err(retry_after) => Some(retry_after.to_std().unwrap_or_default()),
+                }
+            }
+            Self::Db(db) => repo::take_rate_limit(
+                db.conn.as_ref(),
+                &key,
+                seconds(rate.interval),
+                seconds(rate.tolerance),
+            )
+            .await?
+            .map(|it| Duration::from_secs_f64(it.max(0.0))),
+        };
+
+        match retry_after {
+            Some(retry_after) => Err(AppError::RateLimited(retry_after)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Rate {
+    interval: TimeDelta,
+    tolerance: TimeDelta,
+}
+
+impl From<&RateLimitSettings> for Rate {
+    fn from(settings: &RateLimitSettings) -> Self {
+        let capacity = settings.capacity.max(1);
+        let interval = TimeDelta::milliseconds((settings.period * 1000 / capacity as u64) as i64);
+
+        Self {
+            interval,
+            tolerance: interval * (capacity as i32 - 1),
+        }
+    }
+}
+
+impl Rate {
+    fn take(
+        &self,
+        tat: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Result<DateTime<Utc>, TimeDelta> {
+        let tat = tat.map_or(now, |it| it.max(now));
+        let allow_at = tat - self.tolerance;
+
+        if allow_at > now {
+            return Err(allow_at - now);
+        }
+
+        Ok(tat + self.interval)
+    }
+}
+
+// в памяти чистка идет по месту в check, а таблица без нее растет на каждого пользователя
+pub async fn pruner(state: &AppState, settings: &LimiterSettings) -> Result<(), Error> {
+    let Limiter::Db(db) = &state.messages.limiter else {
+        return Ok(());
+    };
+
+    let mut ticker = tokio::time::interval(Duration::from_secs(settings.prune_interval));
+
+    info!("limiter: pruner started");
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {},
+            _ = state.shutdown.wait() => break,
+        }
+
+        match repo::prune_rate_limits(db.conn.as_ref()).await {
+            Ok(0) => {}
+            Ok(pruned) => info!("limiter: pruned {} rate limits", pruned),
+            Err(err) => error!("limiter: {}", err),
+        }
+    }
+
+    info!("limiter: pruner stopped");
+
+    Ok(())
+}
+
+fn seconds(delta: TimeDelta) -> f64 {
+    delta.num_milliseconds() as f64 / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeDelta, Utc};
+
+    use crate::app::limiter::{Rate, settings::RateLimitSettings};
+
+    #[test]
+    fn successfully_take_burst() {
+        let rate = Rate::from(&RateLimitSettings {
+            capacity: 3,
+            period: 3,
+        });
+
+        let now = Utc::now();
+        let mut tat = None;
+
+        for _ in 0..3 {
+            let res = rate.take(tat, now);
+            assert!(res.is_ok());
+            tat = res.ok();
+        }
+
+        let res = rate.take(tat, now);
+        assert_eq!(res, Err(TimeDelta::seconds(1)));
+    }
+
+    #[test]
+    fn successfully_refill() {
+        let rate = Rate::from(&RateLimitSettings {
+            capacity: 1,
+            period: 10,
+        });
+
+        let now = Utc::now();
+
+        let tat = rate.take(None, now).ok();
+        assert!(rate.take(tat, now + TimeDelta::seconds(5)).is_err());
+        assert!(rate.take(tat, now + TimeDelta::seconds(10)).is_ok());
+    }
+}
@@ -0,0 +1,65 @@
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+use tracing::instrument;
+
+use crate::app::error::AppError;
+
+// время берем из базы, чтобы реплики с разъехавшимися часами считали одинаково
+const TAKE: &str = r#"
+INSERT INTO rate_limits (key, tat) VALUES ($1, now() + make_interval(secs => $2))
+ON CONFLICT (key) DO UPDATE
+SET tat = GREATEST(rate_limits.tat, now()) + make_interval(secs => $2)
+WHERE rate_limits.tat <= now() + make_interval(secs => $3)
+RETURNING tat
+"#;
+
+const RETRY_AFTER: &str = r#"
+SELECT EXTRACT(EPOCH FROM tat - now())::float8 - $2 AS retry_after
+FROM rate_limits WHERE key = $1
+"#;
+
+// истекший tat ничем не отличается от отсутствующей строки: бакет уже полон
+const PRUNE: &str = r#"
+DELETE FROM rate_limits WHERE tat < now()
+"#;
+
+#[instrument(skip_all)]
+pub async fn take_rate_limit<T: ConnectionTrait>(
+    db: &T,
+    key: &str,
+    interval: f64,
+    tolerance: f64,
+) -> Result<Option<f64>, AppError> {
+    let taken = db
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            TAKE,
+            [key.into(), interval.into(), tolerance.into()],
+        ))
+        .await?;
+
+    if taken.is_some() {
+        return Ok(None);
+    }
+
+    let retry_after = db
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            RETRY_AFTER,
+            [key.into(), tolerance.into()],
+        ))
+        .await?
+        .map(|it| it.try_get::<f64>("", "retry_after"))
+        .transpose()?
+        .unwrap_or(interval);
+
+    Ok(Some(retry_after))
+}
+
+#[instrument(skip_all)]
+pub async fn prune_rate_limits<T: ConnectionTrait>(db: &T) -> Result<u64, AppError> {
+    let res = db
+        .execute(Statement::from_string(DbBackend::Postgres, PRUNE))
+        .await?;
+
+    Ok(res.rows_affected())
+}
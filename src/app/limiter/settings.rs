@@ -0,0 +1,22 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct LimiterSettings {
+    pub backend: LimiterBackend,
+    // как часто удалять из rate_limits истекшие строки, в секундах
+    pub prune_interval: u64,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum LimiterBackend {
+    Memory,
+    Db,
+}
+
+// capacity запросов подряд, дальше по одному раз в period / capacity секунд
+#[derive(Deserialize, Clone, Copy)]
+pub struct RateLimitSettings {
+    pub capacity: u32,
+    pub period: u64,
+}
@@ -5,15 +5,30 @@ use bzd_lib::{error::Error, settings::NATSSettings};
 
 #[derive(Clone)]
 pub struct MessState {
+    pub nats: async_nats::Client,
     pub js: Arc<JS>,
 }
 
 impl MessState {
     pub async fn new(settings: &NATSSettings) -> Result<Self, Error> {
         let nats = async_nats::connect(&settings.endpoint).await?;
-        let js = Arc::new(jetstream::new(nats));
+        let js = Arc::new(jetstream::new(nats.clone()));
 
-        Ok(Self { js })
+        Ok(Self { nats, js })
+    }
+}
+
+#[cfg(test)]
+impl MessState {
+    // клиент подключается в фоне, брокер не нужен, пока тест ничего не публикует
+    pub async fn stub() -> Result<Self, Error> {
+        let nats = async_nats::ConnectOptions::new()
+            .retry_on_initial_connect()
+            .connect("nats://127.0.0.1:4222")
+            .await?;
+        let js = Arc::new(jetstream::new(nats.clone()));
+
+        Ok(Self { nats, js })
     }
 }
 
@@ -1,14 +1,22 @@
 use bzd_messages_api::messages::messages_service_server::MessagesServiceServer;
+use tonic::service::interceptor::InterceptedService;
 
-use crate::app::{messages::grpc::GrpcMessagesService, state::AppState};
+use crate::app::{auth::AuthInterceptor, messages::grpc::GrpcMessagesService, state::AppState};
 
-mod events;
+pub mod events;
 mod grpc;
+pub mod http;
+pub mod page;
 pub mod repo;
 mod service;
 pub mod settings;
 pub mod state;
 
-pub fn messages_service(state: &AppState) -> MessagesServiceServer<GrpcMessagesService> {
-    MessagesServiceServer::new(GrpcMessagesService::new(state.messages.clone()))
+pub fn messages_service(
+    state: &AppState,
+) -> InterceptedService<MessagesServiceServer<GrpcMessagesService>, AuthInterceptor> {
+    MessagesServiceServer::with_interceptor(
+        GrpcMessagesService::new(state.messages.clone()),
+        state.auth.clone(),
+    )
 }
@@ -1,31 +1,123 @@
-use async_nats::{HeaderMap, jetstream::Context};
-use bytes::BytesMut;
+use async_nats::jetstream::Context;
 use bzd_messages_api::events::message_topic::Type;
-use prost::Message;
 
 use crate::app::{
     error::AppError,
-    messages::{repo::MessageTopicModel, settings::EventsSettings},
+    events::{self, Event, Outgoing},
+    messages::{
+        repo::{MessageModel, MessageTopicModel, StreamModel},
+        settings::EventsSettings,
+    },
 };
 
+pub async fn message(
+    js: &Context,
+    settings: &EventsSettings,
+    message: &MessageModel,
+    tp: bzd_messages_api::events::message::Type,
+) -> Result<(), AppError> {
+    events::publish(js, message_event(settings, message, tp)?).await
+}
+
+pub fn message_event(
+    settings: &EventsSettings,
+    message: &MessageModel,
+    tp: bzd_messages_api::events::message::Type,
+) -> Result<Outgoing, AppError> {
+    let settings = &settings.messages;
+    let event = Event {
+        tp: tp.to_string(),
+        subject: message.message_id,
+        version: message.updated_at,
+        data: bzd_messages_api::events::Message::from(message),
+    };
+
+    events::prepare(&settings.subject, settings.mode, event)
+}
+
 pub async fn message_topic(
     js: &Context,
     settings: &EventsSettings,
     message_topic: &MessageTopicModel,
     tp: Type,
 ) -> Result<(), AppError> {
-    let subject = settings.messages_topics.subject.clone();
-    let mut buf = BytesMut::new();
-    let payload: bzd_messages_api::events::MessageTopic = message_topic.into();
-    payload.encode(&mut buf)?;
+    events::publish(js, message_topic_event(settings, message_topic, tp)?).await
+}
 
-    let mut headers = HeaderMap::new();
-    headers.append("ce_type", tp.to_string());
+pub fn message_topic_event(
+    settings: &EventsSettings,
+    message_topic: &MessageTopicModel,
+    tp: Type,
+) -> Result<Outgoing, AppError> {
+    let settings = &settings.messages_topics;
+    let event = Event {
+        tp: tp.to_string(),
+        subject: message_topic.message_topic_id,
+        version: message_topic.updated_at,
+        data: bzd_messages_api::events::MessageTopic::from(message_topic),
+    };
 
-    js.publish_with_headers(subject, headers, buf.into())
-        .await?;
+    events::prepare(&settings.subject, settings.mode, event)
+}
 
-    Ok(())
+pub async fn stream(
+    js: &Context,
+    settings: &EventsSettings,
+    stream: &StreamModel,
+    tp: bzd_messages_api::events::stream::Type,
+) -> Result<(), AppError> {
+    events::publish(js, stream_event(settings, stream, tp)?).await
+}
+
+pub fn stream_event(
+    settings: &EventsSettings,
+    stream: &StreamModel,
+    tp: bzd_messages_api::events::stream::Type,
+) -> Result<Outgoing, AppError> {
+    let settings = &settings.streams;
+    let event = Event {
+        tp: tp.to_string(),
+        subject: stream.stream_id,
+        version: stream.updated_at,
+        data: bzd_messages_api::events::Stream::from(stream),
+    };
+
+    events::prepare(&settings.subject, settings.mode, event)
+}
+
+mod message {
+    use crate::app::{grpc::ToProtoTimestamp as _, messages::repo::MessageModel};
+
+    impl From<&MessageModel> for bzd_messages_api::events::Message {
+        fn from(message: &MessageModel) -> Self {
+            Self {
+                message_id: Some(message.message_id.into()),
+                text: Some(message.text.clone()),
+                user_id: Some(message.user_id.into()),
+                code: Some(message.code.clone()),
+                hidden_at: message.hidden_at.and_then(|it| it.to_option_proto()),
+                deleted_at: message.deleted_at.and_then(|it| it.to_option_proto()),
+                created_at: message.created_at.to_option_proto(),
+                updated_at: message.updated_at.to_option_proto(),
+            }
+        }
+    }
+}
+
+mod stream {
+    use crate::app::{grpc::ToProtoTimestamp as _, messages::repo::StreamModel};
+
+    impl From<&StreamModel> for bzd_messages_api::events::Stream {
+        fn from(stream: &StreamModel) -> Self {
+            Self {
+                stream_id: Some(stream.stream_id.into()),
+                message_id: Some(stream.message_id.into()),
+                messages_count: Some(stream.messages_count),
+                created_at: stream.created_at.to_option_proto(),
+                updated_at: stream.updated_at.to_option_proto(),
+            }
+        }
+    }
 }
 
 mod message_topic {
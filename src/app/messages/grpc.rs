@@ -3,13 +3,14 @@ use bzd_messages_api::messages::{
     CreateMessageTopicResponse, DeleteMessageTopicRequest, DeleteMessageTopicResponse,
     GetMessageMessagesRequest, GetMessageMessagesResponse, GetMessageRequest, GetMessageResponse,
     GetMessagesRequest, GetMessagesResponse, GetMessagesUsersRequest, GetMessagesUsersResponse,
-    GetStreamsRequest, GetStreamsResponse, GetUserMessagesRequest, GetUserMessagesResponse,
-    GetUserMessagesTopicsRequest, GetUserMessagesTopicsResponse,
+    GetStreamsRequest, GetStreamsResponse, GetTopicMessagesRequest, GetTopicMessagesResponse,
+    GetUserMessagesRequest, GetUserMessagesResponse, GetUserMessagesTopicsRequest,
+    GetUserMessagesTopicsResponse, SetMessageTopicsRequest, SetMessageTopicsResponse,
     messages_service_server::MessagesService,
 };
 use tonic::{Request, Response, Status};
 
-use crate::app::messages::state::MessagesState;
+use crate::app::{auth, messages::state::MessagesState};
 
 pub struct GrpcMessagesService {
     pub state: MessagesState,
@@ -27,7 +28,7 @@ impl MessagesService for GrpcMessagesService {
         &self,
         req: Request<CreateMessageRequest>,
     ) -> Result<Response<CreateMessageResponse>, Status> {
-        let res = create_message::handler(&self.state, req.into_inner()).await?;
+        let res = create_message::handler(&self.state, auth::into_inner(req)).await?;
 
         Ok(Response::new(res))
     }
@@ -36,7 +37,7 @@ impl MessagesService for GrpcMessagesService {
         &self,
         req: Request<GetMessagesRequest>,
     ) -> Result<Response<GetMessagesResponse>, Status> {
-        let res = get_messages::handler(&self.state, req.into_inner()).await?;
+        let res = get_messages::handler(&self.state, auth::into_inner(req)).await?;
 
         Ok(Response::new(res))
     }
@@ -54,7 +55,7 @@ impl MessagesService for GrpcMessagesService {
         &self,
         req: Request<GetMessageMessagesRequest>,
     ) -> Result<Response<GetMessageMessagesResponse>, Status> {
-        let res = get_message_messages::handler(&self.state, req.into_inner()).await?;
+        let res = get_message_messages::handler(&self.state, auth::into_inner(req)).await?;
 
         Ok(Response::new(res))
     }
@@ -68,6 +69,15 @@ impl MessagesService for GrpcMessagesService {
         Ok(Response::new(res))
     }
 
+    async fn get_topic_messages(
+        &self,
+        req: Request<GetTopicMessagesRequest>,
+    ) -> Result<Response<GetTopicMessagesResponse>, Status> {
+        let res = get_topic_messages::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(res))
+    }
+
     async fn get_streams(
         &self,
         req: Request<GetStreamsRequest>,
@@ -99,7 +109,7 @@ impl MessagesService for GrpcMessagesService {
         &self,
         req: Request<CreateMessageTopicRequest>,
     ) -> Result<Response<CreateMessageTopicResponse>, Status> {
-        let res = create_message_topic::handler(&self.state, req.into_inner()).await?;
+        let res = create_message_topic::handler(&self.state, auth::into_inner(req)).await?;
 
         Ok(Response::new(res))
     }
@@ -108,13 +118,22 @@ impl MessagesService for GrpcMessagesService {
         &self,
         req: Request<DeleteMessageTopicRequest>,
     ) -> Result<Response<DeleteMessageTopicResponse>, Status> {
-        delete_message_topic::handler(&self.state, req.into_inner()).await?;
+        delete_message_topic::handler(&self.state, auth::into_inner(req)).await?;
 
         Ok(Response::new(DeleteMessageTopicResponse::default()))
     }
+
+    async fn set_message_topics(
+        &self,
+        req: Request<SetMessageTopicsRequest>,
+    ) -> Result<Response<SetMessageTopicsResponse>, Status> {
+        let res = set_message_topics::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(res))
+    }
 }
 
-mod create_message {
+pub mod create_message {
     use bzd_messages_api::messages::{CreateMessageRequest, CreateMessageResponse};
     use uuid::Uuid;
     use validator::Validate as _;
@@ -122,6 +141,7 @@ mod create_message {
     use crate::app::{
         current_user::CurrentUser,
         error::AppError,
+        limiter::Rpc,
         messages::{
             service::{
                 self,
@@ -133,11 +153,26 @@ mod create_message {
 
     pub async fn handler(
         MessagesState {
-            db, mess, settings, ..
+            db,
+            mess,
+            settings,
+            limiter,
+            cache,
         }: &MessagesState,
         req: CreateMessageRequest,
     ) -> Result<CreateMessageResponse, AppError> {
-        let res = service::create_message(&db.conn, &mess.js, &settings, req.try_into()?).await?;
+        let req: Request = req.try_into()?;
+
+        limiter
+            .check(
+                Rpc::CreateMessage,
+                &req.current_user,
+                &settings.rate_limits.create_message,
+            )
+            .await?;
+
+        let res =
+            service::create_message(&db.conn, &mess.js, cache, limiter, settings, req).await?;
 
         Ok(res.into())
     }
@@ -151,6 +186,11 @@ mod create_message {
                 text: req.text().into(),
                 code: req.code().parse()?,
                 message_id: req.message_id.as_deref().map(Uuid::parse_str).transpose()?,
+                topic_ids: req
+                    .topic_ids
+                    .iter()
+                    .map(|it| it.parse())
+                    .collect::<Result<Vec<Uuid>, _>>()?,
             };
 
             data.validate()?;
@@ -163,35 +203,42 @@ mod create_message {
         fn from(res: Response) -> Self {
             Self {
                 message_id: Some(res.message.message_id.into()),
+                message_topic_ids: res
+                    .messages_topics
+                    .iter()
+                    .map(|it| it.message_topic_id.into())
+                    .collect(),
             }
         }
     }
 }
 
-mod get_messages {
+pub mod get_messages {
     use bzd_messages_api::messages::{
-        GetMessagesRequest, GetMessagesResponse, get_messages_response,
+        GetMessagesRequest, GetMessagesResponse, get_messages_request::Expand,
+        get_messages_response,
     };
     use uuid::Uuid;
 
     use crate::app::{
+        current_user::CurrentUser,
         error::AppError,
         grpc::ToProtoTimestamp,
         messages::{
             repo::message,
             service::{
                 self,
-                get_messages::{Request, Response},
+                get_messages::{Expansion, Request, Response},
             },
             state::MessagesState,
         },
     };
 
     pub async fn handler(
-        MessagesState { db, .. }: &MessagesState,
+        MessagesState { db, cache, .. }: &MessagesState,
         req: GetMessagesRequest,
     ) -> Result<GetMessagesResponse, AppError> {
-        let res = service::get_messages(&db.conn, req.try_into()?).await?;
+        let res = service::get_messages(db.read_conn(), cache, req.try_into()?).await?;
 
         Ok(res.into())
     }
@@ -206,7 +253,29 @@ mod get_messages {
                 .map(|it| it.parse())
                 .collect::<Result<Vec<Uuid>, _>>()?;
 
-            Ok(Self { message_ids })
+            let expand = req
+                .expand()
+                .fold(Expansion::default(), |expand, it| match it {
+                    Expand::Streams => Expansion {
+                        streams: true,
+                        ..expand
+                    },
+                    Expand::MessagesUsers => Expansion {
+                        messages_users: true,
+                        ..expand
+                    },
+                    Expand::MessagesTopics => Expansion {
+                        messages_topics: true,
+                        ..expand
+                    },
+                    Expand::Unspecified => expand,
+                });
+
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                message_ids,
+                expand,
+            })
         }
     }
 
@@ -214,6 +283,9 @@ mod get_messages {
         fn from(res: Response) -> Self {
             Self {
                 messages: res.messages.iter().map(Into::into).collect(),
+                streams: res.streams.iter().map(super::get_streams::stream).collect(),
+                messages_users: res.messages_users.iter().map(Into::into).collect(),
+                messages_topics: res.messages_topics.iter().map(Into::into).collect(),
             }
         }
     }
@@ -226,6 +298,7 @@ mod get_messages {
                 user_id: Some(message.user_id.into()),
                 code: message.code.clone().into(),
                 order: Some(message.created_at.and_utc().timestamp_micros()),
+                deleted_at: message.deleted_at.and_then(|it| it.to_option_proto()),
                 created_at: message.created_at.to_option_proto(),
                 updated_at: message.updated_at.to_option_proto(),
             }
@@ -233,7 +306,7 @@ mod get_messages {
     }
 }
 
-mod get_message {
+pub mod get_message {
     use bzd_messages_api::messages::{
         GetMessageRequest, GetMessageResponse,
         get_message_response::{self},
@@ -255,7 +328,7 @@ mod get_message {
         MessagesState { db, .. }: &MessagesState,
         req: GetMessageRequest,
     ) -> Result<GetMessageResponse, AppError> {
-        let res = service::get_message(&db.conn, req.try_into()?).await?;
+        let res = service::get_message(db.read_conn(), req.try_into()?).await?;
 
         Ok(res.into())
     }
@@ -281,6 +354,7 @@ mod get_message {
                     user_id: Some(message.user_id.into()),
                     code: message.code.clone().into(),
                     order: Some(message.created_at.and_utc().timestamp_micros()),
+                    deleted_at: message.deleted_at.and_then(|it| it.to_option_proto()),
                     created_at: message.created_at.to_option_proto(),
                     updated_at: message.updated_at.to_option_proto(),
                 }),
@@ -289,11 +363,11 @@ mod get_message {
     }
 }
 
-mod get_message_messages {
+pub mod get_message_messages {
     use bzd_messages_api::messages::{GetMessageMessagesRequest, GetMessageMessagesResponse};
-    use uuid::Uuid;
 
     use crate::app::{
+        current_user::CurrentUser,
         error::AppError,
         messages::{
             service::{
@@ -308,7 +382,7 @@ mod get_message_messages {
         MessagesState { db, settings, .. }: &MessagesState,
         req: GetMessageMessagesRequest,
     ) -> Result<GetMessageMessagesResponse, AppError> {
-        let res = service::get_message_messages(&db.conn, req.try_into()?, &settings).await?;
+        let res = service::get_message_messages(db.read_conn(), req.try_into()?, settings).await?;
 
         Ok(res.into())
     }
@@ -318,12 +392,11 @@ mod get_message_messages {
 
         fn try_from(req: GetMessageMessagesRequest) -> Result<Self, Self::Error> {
             Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
                 message_id: req.message_id().parse()?,
-                cursor_message_id: req
-                    .cursor_message_id
-                    .as_deref()
-                    .map(Uuid::parse_str)
-                    .transpose()?,
+                page_token: req.page_token.as_deref().map(str::parse).transpose()?,
+                page_size: req.page_size,
+                exclude_blocked: req.exclude_blocked(),
             })
         }
     }
@@ -331,16 +404,21 @@ mod get_message_messages {
     impl From<Response> for GetMessageMessagesResponse {
         fn from(res: Response) -> Self {
             Self {
-                message_ids: res.messages.iter().map(|it| it.message_id.into()).collect(),
-                cursor_message_id: res.cursor_message.map(|it| it.message_id.into()),
+                message_ids: res
+                    .messages
+                    .items
+                    .iter()
+                    .map(|it| it.message_id.into())
+                    .collect(),
+                older_page_token: res.messages.older.map(|it| it.to_string()),
+                newer_page_token: res.messages.newer.map(|it| it.to_string()),
             }
         }
     }
 }
 
-mod get_user_messages {
+pub mod get_user_messages {
     use bzd_messages_api::messages::{GetUserMessagesRequest, GetUserMessagesResponse};
-    use uuid::Uuid;
 
     use crate::app::{
         error::AppError,
@@ -357,7 +435,7 @@ mod get_user_messages {
         MessagesState { db, settings, .. }: &MessagesState,
         req: GetUserMessagesRequest,
     ) -> Result<GetUserMessagesResponse, AppError> {
-        let res = service::get_user_messages(&db.conn, req.try_into()?, &settings).await?;
+        let res = service::get_user_messages(db.read_conn(), req.try_into()?, settings).await?;
 
         Ok(res.into())
     }
@@ -368,11 +446,8 @@ mod get_user_messages {
         fn try_from(req: GetUserMessagesRequest) -> Result<Self, Self::Error> {
             Ok(Self {
                 user_id: req.user_id().parse()?,
-                cursor_message_id: req
-                    .cursor_message_id
-                    .as_deref()
-                    .map(Uuid::parse_str)
-                    .transpose()?,
+                page_token: req.page_token.as_deref().map(str::parse).transpose()?,
+                page_size: req.page_size,
             })
         }
     }
@@ -382,16 +457,71 @@ mod get_user_messages {
             Self {
                 message_ids: res
                     .messages_users
+                    .items
+                    .iter()
+                    .map(|it| it.message_id.into())
+                    .collect(),
+                older_page_token: res.messages_users.older.map(|it| it.to_string()),
+                newer_page_token: res.messages_users.newer.map(|it| it.to_string()),
+            }
+        }
+    }
+}
+
+pub mod get_topic_messages {
+    use crate::app::{
+        current_user::CurrentUser,
+        error::AppError,
+        messages::{
+            service::{
+                self,
+                get_topic_messages::{Request, Response},
+            },
+            state::MessagesState,
+        },
+    };
+    use bzd_messages_api::messages::{GetTopicMessagesRequest, GetTopicMessagesResponse};
+
+    pub async fn handler(
+        MessagesState { db, settings, .. }: &MessagesState,
+        req: GetTopicMessagesRequest,
+    ) -> Result<GetTopicMessagesResponse, AppError> {
+        let res = service::get_topic_messages(db.read_conn(), req.try_into()?, settings).await?;
+
+        Ok(res.into())
+    }
+
+    impl TryFrom<GetTopicMessagesRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: GetTopicMessagesRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                topic_id: req.topic_id().parse()?,
+                page_token: req.page_token.as_deref().map(str::parse).transpose()?,
+                page_size: req.page_size,
+                exclude_blocked: req.exclude_blocked(),
+            })
+        }
+    }
+
+    impl From<Response> for GetTopicMessagesResponse {
+        fn from(res: Response) -> Self {
+            Self {
+                message_ids: res
+                    .messages_topics
+                    .items
                     .iter()
                     .map(|it| it.message_id.into())
                     .collect(),
-                cursor_message_id: res.cursor_message_user.map(|it| it.message_id.into()),
+                older_page_token: res.messages_topics.older.map(|it| it.to_string()),
+                newer_page_token: res.messages_topics.newer.map(|it| it.to_string()),
             }
         }
     }
 }
 
-mod get_streams {
+pub mod get_streams {
     use bzd_messages_api::messages::{
         GetStreamsRequest, GetStreamsResponse, get_streams_response::Stream,
     };
@@ -401,6 +531,7 @@ mod get_streams {
     use crate::app::{
         error::AppError,
         messages::{
+            repo::{MessageUserModel, StreamModel},
             service::{
                 self,
                 get_streams::{Request, Response},
@@ -410,10 +541,10 @@ mod get_streams {
     };
 
     pub async fn handler(
-        MessagesState { db, .. }: &MessagesState,
+        MessagesState { db, cache, .. }: &MessagesState,
         req: GetStreamsRequest,
     ) -> Result<GetStreamsResponse, AppError> {
-        let res = service::get_streams(&db.conn, req.try_into()?).await?;
+        let res = service::get_streams(db.read_conn(), cache, req.try_into()?).await?;
 
         Ok(res.into())
     }
@@ -435,28 +566,29 @@ mod get_streams {
     impl From<Response> for GetStreamsResponse {
         fn from(res: Response) -> Self {
             Self {
-                streams: res
-                    .streams
-                    .iter()
-                    .map(|(stream, messages_users)| Stream {
-                        stream_id: Some(stream.stream_id.into()),
-                        message_id: Some(stream.message_id.into()),
-                        text: stream.text.clone().into(),
-                        user_ids: messages_users.iter().map(|it| it.user_id.into()).collect(),
-                        messages_count: Some(stream.messages_count),
-                        created_at: Some(Timestamp {
-                            seconds: stream.created_at.and_utc().timestamp(),
-                            nanos: 0,
-                        }),
-                        updated_at: Some(Timestamp {
-                            seconds: stream.updated_at.and_utc().timestamp(),
-                            nanos: 0,
-                        }),
-                    })
-                    .collect(),
+                streams: res.streams.iter().map(stream).collect(),
             }
         }
     }
+
+    // кортеж не локальный тип, поэтому From тут не реализовать
+    pub fn stream((stream, messages_users): &(StreamModel, Vec<MessageUserModel>)) -> Stream {
+        Stream {
+            stream_id: Some(stream.stream_id.into()),
+            message_id: Some(stream.message_id.into()),
+            text: stream.text.clone().into(),
+            user_ids: messages_users.iter().map(|it| it.user_id.into()).collect(),
+            messages_count: Some(stream.messages_count),
+            created_at: Some(Timestamp {
+                seconds: stream.created_at.and_utc().timestamp(),
+                nanos: 0,
+            }),
+            updated_at: Some(Timestamp {
+                seconds: stream.updated_at.and_utc().timestamp(),
+                nanos: 0,
+            }),
+        }
+    }
 }
 
 mod get_messages_users {
@@ -481,7 +613,7 @@ mod get_messages_users {
         MessagesState { db, .. }: &MessagesState,
         req: GetMessagesUsersRequest,
     ) -> Result<GetMessagesUsersResponse, AppError> {
-        let res = service::get_messages_users(&db.conn, req.try_into()?).await?;
+        let res = service::get_messages_users(db.read_conn(), req.try_into()?).await?;
 
         Ok(res.into())
     }
@@ -542,7 +674,7 @@ mod get_user_messages_topics {
         MessagesState { db, .. }: &MessagesState,
         req: GetUserMessagesTopicsRequest,
     ) -> Result<GetUserMessagesTopicsResponse, AppError> {
-        let res = service::get_user_messages_topics(&db.conn, req.try_into()?).await?;
+        let res = service::get_user_messages_topics(db.read_conn(), req.try_into()?).await?;
 
         Ok(res.into())
     }
@@ -589,6 +721,7 @@ mod create_message_topic {
     use crate::app::{
         current_user::CurrentUser,
         error::AppError,
+        limiter::Rpc,
         messages::{
             service::{
                 self,
@@ -600,12 +733,25 @@ mod create_message_topic {
 
     pub async fn handler(
         MessagesState {
-            db, mess, settings, ..
+            db,
+            mess,
+            settings,
+            limiter,
+            ..
         }: &MessagesState,
         req: CreateMessageTopicRequest,
     ) -> Result<CreateMessageTopicResponse, AppError> {
-        let res =
-            service::create_message_topic(&db.conn, &mess.js, settings, req.try_into()?).await?;
+        let req: Request = req.try_into()?;
+
+        limiter
+            .check(
+                Rpc::CreateMessageTopic,
+                &req.current_user,
+                &settings.rate_limits.create_message_topic,
+            )
+            .await?;
+
+        let res = service::create_message_topic(&db.conn, &mess.js, settings, req).await?;
 
         Ok(res.into())
     }
@@ -665,3 +811,65 @@ mod delete_message_topic {
         }
     }
 }
+
+mod set_message_topics {
+    use bzd_messages_api::messages::{SetMessageTopicsRequest, SetMessageTopicsResponse};
+    use uuid::Uuid;
+
+    use crate::app::{
+        current_user::CurrentUser,
+        error::AppError,
+        messages::{
+            service::{
+                self,
+                set_message_topics::{Request, Response},
+            },
+            state::MessagesState,
+        },
+    };
+
+    pub async fn handler(
+        MessagesState {
+            db,
+            mess,
+            settings,
+            limiter,
+            ..
+        }: &MessagesState,
+        req: SetMessageTopicsRequest,
+    ) -> Result<SetMessageTopicsResponse, AppError> {
+        let res =
+            service::set_message_topics(&db.conn, &mess.js, limiter, settings, req.try_into()?)
+                .await?;
+
+        Ok(res.into())
+    }
+
+    impl TryFrom<SetMessageTopicsRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: SetMessageTopicsRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                message_id: req.message_id().parse()?,
+                topic_ids: req
+                    .topic_ids
+                    .iter()
+                    .map(|it| it.parse())
+                    .collect::<Result<Vec<Uuid>, _>>()?,
+            })
+        }
+    }
+
+    impl From<Response> for SetMessageTopicsResponse {
+        fn from(res: Response) -> Self {
+            Self {
+                message_topic_ids: res
+                    .messages_topics
+                    .iter()
+                    .map(|it| it.message_topic_id.into())
+                    .collect(),
+            }
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    routing::get,
+};
+use bzd_messages_api::messages::{
+    CreateMessageRequest, CreateMessageResponse, GetMessageMessagesRequest,
+    GetMessageMessagesResponse, GetMessageRequest, GetMessageResponse, GetMessagesRequest,
+    GetMessagesResponse, GetStreamsRequest, GetStreamsResponse, GetTopicMessagesRequest,
+    GetTopicMessagesResponse, GetUserMessagesRequest, GetUserMessagesResponse,
+    get_messages_request::Expand,
+};
+use serde::Deserialize;
+
+use crate::app::{auth, error::AppError, messages::grpc, state::AppState};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/messages", get(get_messages).post(create_message))
+        .route("/messages/{message_id}", get(get_message))
+        .route("/messages/{message_id}/messages", get(get_message_messages))
+        .route("/users/{user_id}/messages", get(get_user_messages))
+        .route("/topics/{topic_id}/messages", get(get_topic_messages))
+        .route("/streams", get(get_streams))
+}
+
+#[derive(Deserialize)]
+struct IdsQuery {
+    // через запятую: ?message_ids=a,b,c
+    message_ids: String,
+}
+
+impl IdsQuery {
+    fn ids(&self) -> Vec<String> {
+        self.message_ids
+            .split(',')
+            .filter(|it| !it.is_empty())
+            .map(Into::into)
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct ExpandQuery {
+    // через запятую: ?expand=streams,messages_users,messages_topics
+    expand: Option<String>,
+}
+
+impl ExpandQuery {
+    fn values(&self) -> Vec<i32> {
+        self.expand
+            .iter()
+            .flat_map(|it| it.split(','))
+            .filter_map(|it| Expand::from_str_name(&format!("EXPAND_{}", it.to_uppercase())))
+            .map(Into::into)
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct PageQuery {
+    page_token: Option<String>,
+    page_size: Option<u64>,
+    exclude_blocked: Option<bool>,
+}
+
+async fn create_message(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateMessageRequest>,
+) -> Result<Json<CreateMessageResponse>, AppError> {
+    let req = auth::apply(state.auth.authenticate(&headers)?, req);
+    let res = grpc::create_message::handler(&state.messages, req).await?;
+
+    Ok(Json(res))
+}
+
+async fn get_messages(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<IdsQuery>,
+    Query(expand): Query<ExpandQuery>,
+) -> Result<Json<GetMessagesResponse>, AppError> {
+    let req = GetMessagesRequest {
+        current_user_id: None,
+        message_ids: query.ids(),
+        expand: expand.values(),
+    };
+    let req = auth::apply(state.auth.authenticate(&headers)?, req);
+    let res = grpc::get_messages::handler(&state.messages, req).await?;
+
+    Ok(Json(res))
+}
+
+async fn get_message(
+    State(state): State<AppState>,
+    Path(message_id): Path<String>,
+) -> Result<Json<GetMessageResponse>, AppError> {
+    let req = GetMessageRequest {
+        message_id: Some(message_id),
+    };
+    let res = grpc::get_message::handler(&state.messages, req).await?;
+
+    Ok(Json(res))
+}
+
+async fn get_message_messages(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(message_id): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<GetMessageMessagesResponse>, AppError> {
+    let req = GetMessageMessagesRequest {
+        current_user_id: None,
+        message_id: Some(message_id),
+        exclude_blocked: query.exclude_blocked,
+        page_token: query.page_token,
+        page_size: query.page_size,
+    };
+    let req = auth::apply(state.auth.authenticate(&headers)?, req);
+    let res = grpc::get_message_messages::handler(&state.messages, req).await?;
+
+    Ok(Json(res))
+}
+
+async fn get_user_messages(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<GetUserMessagesResponse>, AppError> {
+    let req = GetUserMessagesRequest {
+        user_id: Some(user_id),
+        page_token: query.page_token,
+        page_size: query.page_size,
+    };
+    let res = grpc::get_user_messages::handler(&state.messages, req).await?;
+
+    Ok(Json(res))
+}
+
+async fn get_topic_messages(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(topic_id): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<GetTopicMessagesResponse>, AppError> {
+    let req = GetTopicMessagesRequest {
+        current_user_id: None,
+        topic_id: Some(topic_id),
+        exclude_blocked: query.exclude_blocked,
+        page_token: query.page_token,
+        page_size: query.page_size,
+    };
+    let req = auth::apply(state.auth.authenticate(&headers)?, req);
+    let res = grpc::get_topic_messages::handler(&state.messages, req).await?;
+
+    Ok(Json(res))
+}
+
+async fn get_streams(
+    State(state): State<AppState>,
+    Query(query): Query<IdsQuery>,
+) -> Result<Json<GetStreamsResponse>, AppError> {
+    let req = GetStreamsRequest {
+        message_ids: query.ids(),
+    };
+    let res = grpc::get_streams::handler(&state.messages, req).await?;
+
+    Ok(Json(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use axum::{
+        body::Body,
+        http::{Method, Request, Response, StatusCode, header},
+    };
+    use bzd_lib::error::Error;
+    use chrono::{TimeDelta, Utc};
+    use jsonwebtoken::{EncodingKey, Header};
+    use serde_json::json;
+    use tower::ServiceExt as _;
+    use uuid::Uuid;
+
+    use crate::app::{limiter::Limiter, messages::http, state::AppState};
+
+    async fn call(state: AppState, req: Request<Body>) -> Result<Response<Body>, Error> {
+        let router = http::router().with_state(state);
+
+        Ok(router.oneshot(req).await?)
+    }
+
+    fn create_message(token: &str) -> Result<Request<Body>, Error> {
+        let body = json!({ "text": "TEXT", "code": Uuid::now_v7() });
+
+        Ok(Request::builder()
+            .method(Method::POST)
+            .uri("/messages")
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))?)
+    }
+
+    fn token(sub: Uuid, key: &str) -> Result<String, Error> {
+        let claims = json!({ "sub": sub, "exp": u64::MAX / 2 });
+
+        Ok(jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(key.as_bytes()),
+        )?)
+    }
+
+    #[tokio::test]
+    async fn failed_invalid_token() -> Result<(), Error> {
+        let state = AppState::stub().await?;
+
+        let res = call(state, create_message(&token(Uuid::now_v7(), "WRONG")?)?).await?;
+
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn failed_invalid_message_id() -> Result<(), Error> {
+        let state = AppState::stub().await?;
+
+        let req = Request::builder()
+            .uri("/messages/not-a-uuid")
+            .body(Body::empty())?;
+        let res = call(state, req).await?;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn failed_rate_limited() -> Result<(), Error> {
+        let mut state = AppState::stub().await?;
+        let user_id = Uuid::now_v7();
+
+        // бакет уже исчерпан на минуту вперед, до базы и NATS запрос не дойдет
+        state.messages.limiter = Limiter::Memory(Arc::new(Mutex::new(HashMap::from([(
+            format!("CreateMessage:{user_id}"),
+            Utc::now() + TimeDelta::minutes(1),
+        )]))));
+
+        let res = call(state, create_message(&token(user_id, "SECRET")?)?).await?;
+
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(
+            res.headers()
+                .get(header::RETRY_AFTER)
+                .and_then(|it| it.to_str().ok())
+                .and_then(|it| it.parse::<u64>().ok())
+                .is_some_and(|it| it > 0)
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use std::{fmt, str::FromStr};
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use sea_orm::{
+    ColumnTrait, EntityTrait, QueryFilter as _, QueryOrder as _, QuerySelect as _, Select,
+};
+use uuid::Uuid;
+
+use crate::app::error::AppError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Older,
+    Newer,
+}
+
+// Для клиента токен непрозрачен: направление и граничный ключ, сама граница в страницу не входит
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PageToken {
+    pub direction: Direction,
+    pub key: Uuid,
+}
+
+impl PageToken {
+    pub fn older(key: Uuid) -> Self {
+        Self {
+            direction: Direction::Older,
+            key,
+        }
+    }
+
+    pub fn newer(key: Uuid) -> Self {
+        Self {
+            direction: Direction::Newer,
+            key,
+        }
+    }
+
+    pub fn admits(&self, key: Uuid) -> bool {
+        match self.direction {
+            Direction::Older => key < self.key,
+            Direction::Newer => key > self.key,
+        }
+    }
+}
+
+impl fmt::Display for PageToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buf = Vec::with_capacity(17);
+        buf.push(match self.direction {
+            Direction::Older => 0,
+            Direction::Newer => 1,
+        });
+        buf.extend_from_slice(self.key.as_bytes());
+
+        f.write_str(&URL_SAFE_NO_PAD.encode(buf))
+    }
+}
+
+impl FromStr for PageToken {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let buf = URL_SAFE_NO_PAD
+            .decode(s)
+            .map_err(|_| AppError::Validation)?;
+
+        let (direction, key) = buf.split_first().ok_or(AppError::Validation)?;
+
+        let direction = match direction {
+            0 => Direction::Older,
+            1 => Direction::Newer,
+            _ => return Err(AppError::Validation),
+        };
+
+        Ok(Self {
+            direction,
+            key: Uuid::from_slice(key)?,
+        })
+    }
+}
+
+pub fn limit(page_size: Option<u64>, max: u64) -> u64 {
+    page_size.filter(|it| *it > 0).map_or(max, |it| it.min(max))
+}
+
+pub struct Page<T> {
+    // от новых к старым независимо от направления
+    pub items: Vec<T>,
+    pub older: Option<PageToken>,
+    pub newer: Option<PageToken>,
+}
+
+impl<T> Page<T> {
+    // items в порядке выборки от границы токена, на один больше limit, если есть продолжение
+    pub fn new(
+        mut items: Vec<T>,
+        limit: u64,
+        token: Option<PageToken>,
+        key: fn(&T) -> Uuid,
+    ) -> Self {
+        let limit = limit as usize;
+        let has_more = items.len() > limit;
+        items.truncate(limit);
+
+        let newer_direction = token.is_some_and(|it| it.direction == Direction::Newer);
+
+        if newer_direction {
+            items.reverse();
+        }
+
+        // листая к новым, старее страницы всегда есть хотя бы сама граница
+        let older = if newer_direction {
+            items.last().map(key).or(token.map(|it| it.key))
+        } else if has_more {
+            items.last().map(key)
+        } else {
+            None
+        };
+
+        // верхний токен отдаём всегда, по нему клиент дочитывает новые ответы
+        let newer = match items.first() {
+            Some(first) => Some(key(first)),
+            None if newer_direction => token.map(|it| it.key),
+            None => None,
+        };
+
+        Self {
+            items,
+            older: older.map(PageToken::older),
+            newer: newer.map(PageToken::newer),
+        }
+    }
+}
+
+// Выборка идёт от границы токена: к старым по убыванию ключа, к новым по возрастанию
+pub fn paginate<E: EntityTrait>(
+    query: Select<E>,
+    column: impl ColumnTrait,
+    page_token: Option<PageToken>,
+    limit: u64,
+) -> Select<E> {
+    let query = match page_token {
+        Some(PageToken {
+            direction: Direction::Newer,
+            key,
+        }) => query.filter(column.gt(key)).order_by_asc(column),
+        Some(PageToken {
+            direction: Direction::Older,
+            key,
+        }) => query.filter(column.lt(key)).order_by_desc(column),
+        None => query.order_by_desc(column),
+    };
+
+    query.limit(limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use bzd_lib::error::Error;
+    use uuid::Uuid;
+
+    use crate::app::messages::page::{Page, PageToken, limit};
+
+    #[test]
+    fn test_ok_page_token_roundtrip() -> Result<(), Error> {
+        let token = PageToken::newer(Uuid::now_v7());
+
+        assert_eq!(token.to_string().parse::<PageToken>()?, token);
+        assert!("garbage".parse::<PageToken>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ok_page_limit() {
+        assert_eq!(limit(None, 25), 25);
+        assert_eq!(limit(Some(0), 25), 25);
+        assert_eq!(limit(Some(10), 25), 10);
+        assert_eq!(limit(Some(100), 25), 25);
+    }
+
+    #[test]
+    fn test_ok_page_directions() {
+        let ids: Vec<Uuid> = (0..5).map(|_| Uuid::now_v7()).collect();
+
+        // первая страница: от новых к старым, есть продолжение
+        let older: Vec<Uuid> = ids.iter().rev().take(3).copied().collect();
+        let page = Page::new(older, 2, None, |it| *it);
+
+        assert_eq!(page.items, vec![ids[4], ids[3]]);
+        assert_eq!(page.older, Some(PageToken::older(ids[3])));
+        assert_eq!(page.newer, Some(PageToken::newer(ids[4])));
+
+        // к новым от ids[1]: выборка по возрастанию
+        let token = PageToken::newer(ids[1]);
+        let newer: Vec<Uuid> = ids[2..].to_vec();
+        let page = Page::new(newer, 2, Some(token), |it| *it);
+
+        assert_eq!(page.items, vec![ids[3], ids[2]]);
+        assert_eq!(page.older, Some(PageToken::older(ids[2])));
+        assert_eq!(page.newer, Some(PageToken::newer(ids[3])));
+    }
+}
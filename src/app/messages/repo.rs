@@ -1,11 +1,16 @@
+use chrono::Utc;
 use sea_orm::{
     ActiveModelTrait as _, ColumnTrait as _, ConnectionTrait, EntityTrait as _,
-    IntoActiveModel as _, JoinType, ModelTrait as _, QueryFilter as _, QueryOrder as _,
-    QuerySelect as _, QueryTrait as _, prelude::Expr, sea_query::OnConflict,
+    IntoActiveModel as _, JoinType, ModelTrait as _, QueryFilter as _, QuerySelect as _,
+    QueryTrait as _, TryInsertResult, prelude::Expr, sea_query::OnConflict,
 };
+use tracing::instrument;
 use uuid::Uuid;
 
-use crate::app::error::AppError;
+use crate::app::{
+    error::AppError,
+    messages::page::{PageToken, paginate},
+};
 
 pub mod message;
 pub mod message_stream;
@@ -13,6 +18,8 @@ pub mod message_topic;
 pub mod message_user;
 pub mod stream;
 pub mod topic;
+pub mod user_block;
+pub mod user_suspension;
 
 pub type MessageModel = message::Model;
 pub type TopicModel = topic::Model;
@@ -20,7 +27,10 @@ pub type MessageStreamModel = message_stream::Model;
 pub type MessageTopicModel = message_topic::Model;
 pub type MessageUserModel = message_user::Model;
 pub type StreamModel = stream::Model;
+pub type UserBlockModel = user_block::Model;
+pub type UserSuspensionModel = user_suspension::Model;
 
+#[instrument(skip_all)]
 pub async fn create_message<T: ConnectionTrait>(
     db: &T,
     model: MessageModel,
@@ -30,6 +40,7 @@ pub async fn create_message<T: ConnectionTrait>(
     Ok(message)
 }
 
+#[instrument(skip_all)]
 pub async fn get_message_by_id<T: ConnectionTrait>(
     db: &T,
     message_id: Uuid,
@@ -42,6 +53,7 @@ pub async fn get_message_by_id<T: ConnectionTrait>(
     Ok(message)
 }
 
+#[instrument(skip_all)]
 pub async fn get_messages_by_ids<T: ConnectionTrait>(
     db: &T,
     message_ids: Vec<Uuid>,
@@ -54,6 +66,7 @@ pub async fn get_messages_by_ids<T: ConnectionTrait>(
     Ok(messages)
 }
 
+#[instrument(skip_all)]
 pub async fn create_stream<T: ConnectionTrait>(
     db: &T,
     model: stream::Model,
@@ -78,31 +91,35 @@ pub async fn create_stream<T: ConnectionTrait>(
     Ok(stream)
 }
 
+#[instrument(skip_all)]
 pub async fn create_message_stream<T: ConnectionTrait>(
     db: &T,
     model: message_stream::Model,
-) -> Result<(), AppError> {
-    message_stream::Entity::insert(model.into_active_model())
+) -> Result<Option<message_stream::Model>, AppError> {
+    let res = message_stream::Entity::insert(model.clone().into_active_model())
         .on_conflict(OnConflict::new().do_nothing().to_owned())
         .do_nothing()
         .exec(db)
         .await?;
-    Ok(())
+
+    Ok(matches!(res, TryInsertResult::Inserted(_)).then_some(model))
 }
 
+#[instrument(skip_all)]
 pub async fn create_message_user<T: ConnectionTrait>(
     db: &T,
     model: MessageUserModel,
-) -> Result<(), AppError> {
-    message_user::Entity::insert(model.into_active_model())
+) -> Result<Option<MessageUserModel>, AppError> {
+    let res = message_user::Entity::insert(model.clone().into_active_model())
         .on_conflict(OnConflict::new().do_nothing().to_owned())
         .do_nothing()
         .exec(db)
         .await?;
 
-    Ok(())
+    Ok(matches!(res, TryInsertResult::Inserted(_)).then_some(model))
 }
 
+#[instrument(skip_all)]
 pub async fn find_stream_by_message_id<T: ConnectionTrait>(
     db: &T,
     message_id: Uuid,
@@ -115,13 +132,15 @@ pub async fn find_stream_by_message_id<T: ConnectionTrait>(
     Ok(stream)
 }
 
+#[instrument(skip_all)]
 pub async fn get_messages_by_stream_id<T: ConnectionTrait>(
     db: &T,
     stream_id: Uuid,
-    cursor_message_id: Option<Uuid>,
+    page_token: Option<PageToken>,
+    exclude_user_ids: Vec<Uuid>,
     limit: u64,
 ) -> Result<Vec<message::Model>, AppError> {
-    let messages = message::Entity::find()
+    let query = message::Entity::find()
         .join(
             JoinType::InnerJoin,
             message::Entity::belongs_to(message_stream::Entity)
@@ -130,53 +149,66 @@ pub async fn get_messages_by_stream_id<T: ConnectionTrait>(
                 .into(),
         )
         .filter(message_stream::Column::StreamId.eq(stream_id))
-        .apply_if(cursor_message_id, |query, v| {
-            query.filter(message::Column::MessageId.lte(v))
-        })
-        .cursor_by(message::Column::MessageId)
-        .last(limit)
+        .filter(message::Column::HiddenAt.is_null())
+        .apply_if(
+            (!exclude_user_ids.is_empty()).then_some(exclude_user_ids),
+            |query, v| query.filter(message::Column::UserId.is_not_in(v)),
+        );
+
+    let messages = paginate(query, message::Column::MessageId, page_token, limit)
         .all(db)
         .await?;
 
     Ok(messages)
 }
 
+#[instrument(skip_all)]
 pub async fn increase_stream_messages_count<T: ConnectionTrait>(
     db: &T,
     message_id: Uuid,
-) -> Result<(), AppError> {
-    stream::Entity::update_many()
+) -> Result<Option<stream::Model>, AppError> {
+    let mut streams = stream::Entity::update_many()
         .col_expr(
             stream::Column::MessagesCount,
             Expr::col(stream::Column::MessagesCount).add(1),
         )
+        // updated_at входит в Nats-Msg-Id эвента stream.updated, без него JetStream
+        // посчитает следующие обновления дублями
+        .col_expr(
+            stream::Column::UpdatedAt,
+            Expr::value(Utc::now().naive_utc()),
+        )
         .filter(stream::Column::MessageId.eq(message_id))
-        .exec(db)
+        .exec_with_returning(db)
         .await?;
 
-    Ok(())
+    Ok(streams.pop())
 }
 
+#[instrument(skip_all)]
 pub async fn get_messages_users_by_user_id<T: ConnectionTrait>(
     db: &T,
     user_id: Uuid,
-    cursor_message_id: Option<Uuid>,
+    page_token: Option<PageToken>,
     limit: u64,
 ) -> Result<Vec<MessageUserModel>, AppError> {
-    let messages_users = message_user::Entity::find()
+    let query = message_user::Entity::find()
         .filter(message_user::Column::UserId.eq(user_id))
-        .filter(message_user::Column::IsOwned.eq(true))
-        .apply_if(cursor_message_id, |query, v| {
-            query.filter(message_user::Column::MessageUserId.lte(v))
-        })
-        .order_by_desc(message_user::Column::MessageUserId)
-        .limit(limit)
-        .all(db)
-        .await?;
+        .filter(message_user::Column::IsOwned.eq(true));
+
+    let messages_users = paginate(
+        query,
+        message_user::Column::MessageUserId,
+        page_token,
+        limit,
+    )
+    .all(db)
+    .await?;
 
     Ok(messages_users)
 }
 
+#[instrument(skip_all)]
 pub async fn get_streams_by_message_ids<T: ConnectionTrait>(
     db: &T,
     message_ids: Vec<Uuid>,
@@ -197,6 +229,7 @@ pub async fn get_streams_by_message_ids<T: ConnectionTrait>(
     Ok(streams)
 }
 
+#[instrument(skip_all)]
 pub async fn get_messages_users_by_message_ids<T: ConnectionTrait>(
     db: &T,
     message_ids: Vec<Uuid>,
@@ -209,6 +242,7 @@ pub async fn get_messages_users_by_message_ids<T: ConnectionTrait>(
     Ok(messages_users)
 }
 
+#[instrument(skip_all)]
 pub async fn get_topic_by_id<T: ConnectionTrait>(
     db: &T,
     topic_id: Uuid,
@@ -221,6 +255,20 @@ pub async fn get_topic_by_id<T: ConnectionTrait>(
     Ok(topic)
 }
 
+#[instrument(skip_all)]
+pub async fn get_topics_by_ids<T: ConnectionTrait>(
+    db: &T,
+    topic_ids: Vec<Uuid>,
+) -> Result<Vec<TopicModel>, AppError> {
+    let topics = topic::Entity::find()
+        .filter(topic::Column::TopicId.is_in(topic_ids))
+        .all(db)
+        .await?;
+
+    Ok(topics)
+}
+
+#[instrument(skip_all)]
 pub async fn get_topics_by_user_id<T: ConnectionTrait>(
     db: &T,
     user_id: Uuid,
@@ -233,6 +281,7 @@ pub async fn get_topics_by_user_id<T: ConnectionTrait>(
     Ok(topics)
 }
 
+#[instrument(skip_all)]
 pub async fn get_messages_topics_by_message_ids_and_topics_ids<T: ConnectionTrait>(
     db: &T,
     message_ids: Vec<Uuid>,
@@ -247,6 +296,59 @@ pub async fn get_messages_topics_by_message_ids_and_topics_ids<T: ConnectionTrai
     Ok(messages_topics)
 }
 
+#[instrument(skip_all)]
+pub async fn get_messages_topics_by_message_ids_and_user_id<T: ConnectionTrait>(
+    db: &T,
+    message_ids: Vec<Uuid>,
+    user_id: Uuid,
+) -> Result<Vec<MessageTopicModel>, AppError> {
+    let messages_topics = message_topic::Entity::find()
+        .join(
+            JoinType::InnerJoin,
+            message_topic::Entity::belongs_to(topic::Entity)
+                .to(topic::Column::TopicId)
+                .from(message_topic::Column::TopicId)
+                .into(),
+        )
+        .filter(message_topic::Column::MessageId.is_in(message_ids))
+        .filter(topic::Column::UserId.eq(user_id))
+        .all(db)
+        .await?;
+
+    Ok(messages_topics)
+}
+
+#[instrument(skip_all)]
+pub async fn get_messages_topics_by_topic_id<T: ConnectionTrait>(
+    db: &T,
+    topic_id: Uuid,
+    page_token: Option<PageToken>,
+    exclude_user_ids: Vec<Uuid>,
+    limit: u64,
+) -> Result<Vec<MessageTopicModel>, AppError> {
+    let query = message_topic::Entity::find()
+        .join(
+            JoinType::InnerJoin,
+            message_topic::Entity::belongs_to(message::Entity)
+                .to(message::Column::MessageId)
+                .from(message_topic::Column::MessageId)
+                .into(),
+        )
+        .filter(message_topic::Column::TopicId.eq(topic_id))
+        .filter(message::Column::HiddenAt.is_null())
+        .apply_if(
+            (!exclude_user_ids.is_empty()).then_some(exclude_user_ids),
+            |query, v| query.filter(message::Column::UserId.is_not_in(v)),
+        );
+
+    let messages_topics = paginate(query, message_topic::Column::MessageId, page_token, limit)
+        .all(db)
+        .await?;
+
+    Ok(messages_topics)
+}
+
+#[instrument(skip_all)]
 pub async fn create_message_topic<T: ConnectionTrait>(
     db: &T,
     model: MessageTopicModel,
@@ -274,6 +376,7 @@ pub async fn create_message_topic<T: ConnectionTrait>(
     Ok(message_topic)
 }
 
+#[instrument(skip_all)]
 pub async fn get_message_topic_by_id<T: ConnectionTrait>(
     db: &T,
     message_topic_id: Uuid,
@@ -286,6 +389,7 @@ pub async fn get_message_topic_by_id<T: ConnectionTrait>(
     Ok(message_topic)
 }
 
+#[instrument(skip_all)]
 pub async fn delete_message_topic<T: ConnectionTrait>(
     db: &T,
     message_topic: MessageTopicModel,
@@ -294,3 +398,61 @@ pub async fn delete_message_topic<T: ConnectionTrait>(
 
     Ok(())
 }
+
+#[instrument(skip_all)]
+pub async fn find_user_block<T: ConnectionTrait>(
+    db: &T,
+    user_id: Uuid,
+    blocked_user_id: Uuid,
+) -> Result<Option<UserBlockModel>, AppError> {
+    let user_block = user_block::Entity::find()
+        .filter(user_block::Column::UserId.eq(user_id))
+        .filter(user_block::Column::BlockedUserId.eq(blocked_user_id))
+        .one(db)
+        .await?;
+
+    Ok(user_block)
+}
+
+#[instrument(skip_all)]
+pub async fn get_users_blocks_by_user_id<T: ConnectionTrait>(
+    db: &T,
+    user_id: Uuid,
+) -> Result<Vec<UserBlockModel>, AppError> {
+    let users_blocks = user_block::Entity::find()
+        .filter(user_block::Column::UserId.eq(user_id))
+        .all(db)
+        .await?;
+
+    Ok(users_blocks)
+}
+
+// скрытые тоже, выборка для модерации
+#[instrument(skip_all)]
+pub async fn get_messages_by_user_id<T: ConnectionTrait>(
+    db: &T,
+    user_id: Uuid,
+    page_token: Option<PageToken>,
+    limit: u64,
+) -> Result<Vec<MessageModel>, AppError> {
+    let query = message::Entity::find().filter(message::Column::UserId.eq(user_id));
+
+    let messages = paginate(query, message::Column::MessageId, page_token, limit)
+        .all(db)
+        .await?;
+
+    Ok(messages)
+}
+
+#[instrument(skip_all)]
+pub async fn find_user_suspension<T: ConnectionTrait>(
+    db: &T,
+    user_id: Uuid,
+) -> Result<Option<UserSuspensionModel>, AppError> {
+    let user_suspension = user_suspension::Entity::find()
+        .filter(user_suspension::Column::UserId.eq(user_id))
+        .one(db)
+        .await?;
+
+    Ok(user_suspension)
+}
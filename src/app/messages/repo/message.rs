@@ -1,7 +1,8 @@
 use chrono::Utc;
 use sea_orm::entity::prelude::*;
+use serde::Serialize;
 
-#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize)]
 #[sea_orm(table_name = "messages")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -9,6 +10,9 @@ pub struct Model {
     pub user_id: Uuid,
     pub text: String,
     pub code: String,
+    pub hidden_at: Option<DateTime>,
+    // надгробие после удаления данных автора: текста и автора уже нет, место в стриме осталось
+    pub deleted_at: Option<DateTime>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
@@ -23,6 +27,8 @@ impl Model {
             user_id,
             text,
             code,
+            hidden_at: None,
+            deleted_at: None,
             created_at: now,
             updated_at: now,
         }
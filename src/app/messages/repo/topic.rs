@@ -10,6 +10,20 @@ pub struct Model {
     pub updated_at: DateTime,
 }
 
+impl Model {
+    #[cfg(test)]
+    pub fn stub() -> Self {
+        let now = chrono::Utc::now().naive_utc();
+
+        Self {
+            topic_id: Uuid::now_v7(),
+            user_id: Uuid::now_v7(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {}
 
@@ -0,0 +1,27 @@
+use chrono::Utc;
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "user_suspensions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_suspension_id: Uuid,
+    pub user_id: Uuid,
+    pub reason: Option<String>,
+    pub until: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl Model {
+    pub fn is_active(&self) -> bool {
+        self.until
+            .is_none_or(|until| until > Utc::now().naive_utc())
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
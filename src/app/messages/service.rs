@@ -1,86 +1,209 @@
 use async_nats::jetstream::Context;
-use bzd_messages_api::events::message_topic::Type;
-use sea_orm::{DbConn, TransactionTrait as _};
+use bzd_messages_api::events::{message_topic::Type, stream::Type as StreamType};
+use sea_orm::{ConnectionTrait, DbConn, TransactionTrait as _};
+use tracing::instrument;
+use uuid::Uuid;
 
 use crate::app::{
+    audit::{self, repo::AuditLogAction},
+    cache::CacheState,
+    current_user::CurrentUser,
     error::AppError,
+    limiter::{Limiter, Rpc},
     messages::{
         events,
-        repo::{self, MessageModel, MessageStreamModel, MessageTopicModel, MessageUserModel},
+        page::{self, Page},
+        repo::{
+            self, MessageModel, MessageStreamModel, MessageTopicModel, MessageUserModel, TopicModel,
+        },
         settings::MessagesSettings,
     },
+    metrics,
 };
 
+#[instrument(skip_all)]
 pub async fn create_message(
     db: &DbConn,
-    _js: &Context,
-    _settings: &MessagesSettings,
+    js: &Context,
+    cache: &CacheState,
+    limiter: &Limiter,
+    settings: &MessagesSettings,
     req: create_message::Request,
 ) -> Result<create_message::Response, AppError> {
     let current_user = req.current_user.ok_or(AppError::Forbidden)?;
 
+    if repo::find_user_suspension(db, current_user.user_id)
+        .await?
+        .is_some_and(|it| it.is_active())
+    {
+        return Err(AppError::Forbidden);
+    }
+
     let tx = db.begin().await?;
 
+    let topics = get_owned_topics(&tx, &current_user, req.topic_ids).await?;
+
     let message = MessageModel::new(current_user.user_id, req.text, req.code.to_string());
     let message = repo::create_message(&tx, message).await?;
 
+    audit::record(
+        &tx,
+        Some(current_user),
+        AuditLogAction::Created,
+        None,
+        Some(&message),
+    )
+    .await?;
+
+    let mut is_new_stream = false;
+
     if let Some(message_id) = req.message_id {
         let source_message = repo::get_message_by_id(&tx, message_id).await?;
 
+        // на надгробие ответить нельзя, но в ленте и стриме оно остается
+        if source_message.hidden_at.is_some() || source_message.deleted_at.is_some() {
+            return Err(AppError::NotFound);
+        }
+
+        if repo::find_user_block(&tx, source_message.user_id, current_user.user_id)
+            .await?
+            .is_some()
+        {
+            return Err(AppError::Forbidden);
+        }
+
         let stream = repo::stream::Model::new(message_id, source_message.text.clone());
+        let stream_id = stream.stream_id;
         let stream = repo::create_stream(&tx, stream)
             .await?
             .ok_or(AppError::Unreachable)?;
+        is_new_stream = stream.stream_id == stream_id;
 
-        repo::create_message_stream(
-            &tx,
-            MessageStreamModel::new(source_message.message_id, stream.stream_id),
-        )
-        .await?;
+        if is_new_stream {
+            audit::record_ids(&tx, Some(current_user), AuditLogAction::Created, &stream).await?;
+        }
 
-        repo::create_message_stream(
-            &tx,
+        for message_stream in [
+            MessageStreamModel::new(source_message.message_id, stream.stream_id),
             MessageStreamModel::new(message.message_id, stream.stream_id),
-        )
-        .await?;
+        ] {
+            // уже существующие связи повторно в журнал не пишем
+            if let Some(message_stream) = repo::create_message_stream(&tx, message_stream).await? {
+                audit::record(
+                    &tx,
+                    Some(current_user),
+                    AuditLogAction::Created,
+                    None,
+                    Some(&message_stream),
+                )
+                .await?;
+            }
+        }
 
-        repo::create_message_user(
-            &tx,
+        for message_user in [
             MessageUserModel::new(source_message.message_id, message.user_id, false),
+            MessageUserModel::new(source_message.message_id, source_message.user_id, false),
+        ] {
+            if let Some(message_user) = repo::create_message_user(&tx, message_user).await? {
+                audit::record(
+                    &tx,
+                    Some(current_user),
+                    AuditLogAction::Created,
+                    None,
+                    Some(&message_user),
+                )
+                .await?;
+            }
+        }
+
+        // TODO: скорее всего стоит вытащить из транзакции, т.к. это по сути неявный лок на запись,
+        // а если много юзеров будет постить в один стрим, это будет растягивать транзакцию, а это будет сильнее пул
+        // утилизировать
+        // счетчик пишется мимо транзакции, поэтому и его запись в журнале идет тем же соединением
+        if let Some(stream) = repo::increase_stream_messages_count(db, message_id).await? {
+            audit::record_ids(db, Some(current_user), AuditLogAction::Updated, &stream).await?;
+        }
+    } else if let Some(message_user) = repo::create_message_user(
+        &tx,
+        MessageUserModel::new(message.message_id, message.user_id, true),
+    )
+    .await?
+    {
+        audit::record(
+            &tx,
+            Some(current_user),
+            AuditLogAction::Created,
+            None,
+            Some(&message_user),
         )
         .await?;
+    };
+
+    let mut messages_topics = vec![];
+
+    for topic in topics {
+        // связь из CreateMessage стоит столько же, сколько вызов CreateMessageTopic
+        limiter
+            .check(
+                Rpc::CreateMessageTopic,
+                &Some(current_user),
+                &settings.rate_limits.create_message_topic,
+            )
+            .await?;
 
-        repo::create_message_user(
+        let message_topic = repo::create_message_topic(
             &tx,
-            MessageUserModel::new(source_message.message_id, source_message.user_id, false),
+            MessageTopicModel::new(message.message_id, topic.topic_id),
         )
         .await?;
 
-        // TODO: скорее всего стоит вытащить из транзакции, т.к. это по сути неявный лок на запись,
-        // а если много юзеров будет постить в один стрим, это будет растягивать транзакцию, а это будет сильнее пул
-        // утилизировать
-        repo::increase_stream_messages_count(db, message_id).await?;
-    } else {
-        repo::create_message_user(
+        audit::record(
             &tx,
-            MessageUserModel::new(message.message_id, message.user_id, true),
+            Some(current_user),
+            AuditLogAction::Created,
+            None,
+            Some(&message_topic),
         )
         .await?;
-    };
+
+        messages_topics.push(message_topic);
+    }
 
     tx.commit().await?;
 
+    metrics::message_created();
+    if is_new_stream {
+        metrics::stream_created();
+    }
+
+    if let Some(message_id) = req.message_id
+        && let Some(stream) = repo::find_stream_by_message_id(db, message_id).await?
+    {
+        cache.streams.invalidate(message_id, stream.updated_at);
+        events::stream(js, &settings.events, &stream, StreamType::Updated).await?;
+    }
+
     // TODO: нужно сделать чтобы оно не терялось (и инкриз и отсылку эвентов.. аутбокс?)
     // events::publish_message(db, js, &settings.events, message.message_id, Type::Created).await?;
 
-    Ok(create_message::Response { message })
+    for message_topic in &messages_topics {
+        events::message_topic(js, &settings.events, message_topic, Type::Created).await?;
+    }
+
+    Ok(create_message::Response {
+        message,
+        messages_topics,
+    })
 }
 
 pub mod create_message {
     use uuid::Uuid;
     use validator::Validate;
 
-    use crate::app::{current_user::CurrentUser, messages::repo::message};
+    use crate::app::{
+        current_user::CurrentUser,
+        messages::repo::{MessageTopicModel, message},
+    };
 
     #[derive(Validate)]
     pub struct Request {
@@ -89,42 +212,258 @@ pub mod create_message {
         pub text: String,
         pub code: Uuid,
         pub message_id: Option<Uuid>,
+        pub topic_ids: Vec<Uuid>,
     }
 
     pub struct Response {
         pub message: message::Model,
+        pub messages_topics: Vec<MessageTopicModel>,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bzd_lib::error::Error;
+        use chrono::Utc;
+        use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+        use uuid::Uuid;
+
+        use crate::app::{
+            cache::{CacheState, settings::CacheSettings},
+            current_user::CurrentUser,
+            error::AppError,
+            limiter::Limiter,
+            mess::MessState,
+            messages::{
+                repo::{MessageModel, UserBlockModel, UserSuspensionModel},
+                service::{self, create_message::Request},
+                settings::MessagesSettings,
+            },
+        };
+
+        #[tokio::test]
+        async fn test_forbidden_create_message_reply_blocked() -> Result<(), Error> {
+            let settings = MessagesSettings::stub();
+            let mess = MessState::stub().await?;
+            let cache = CacheState::new(&CacheSettings::stub());
+            let limiter = Limiter::Memory(Default::default());
+            let now = Utc::now().naive_utc();
+
+            let source_message = MessageModel::new(Uuid::now_v7(), "TEXT".into(), "CODE".into());
+            let message = MessageModel::new(Uuid::now_v7(), "REPLY".into(), "CODE".into());
+
+            // автор исходного сообщения заблокировал отвечающего
+            let user_block = UserBlockModel {
+                user_block_id: Uuid::now_v7(),
+                user_id: source_message.user_id,
+                blocked_user_id: message.user_id,
+                created_at: now,
+                updated_at: now,
+            };
+
+            let req = Request {
+                current_user: Some(CurrentUser {
+                    user_id: message.user_id,
+                }),
+                text: message.text.clone(),
+                code: Uuid::now_v7(),
+                message_id: Some(source_message.message_id),
+                topic_ids: vec![],
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<UserSuspensionModel>::new()])
+                .append_query_results([vec![message.clone()]])
+                .append_exec_results([MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                }])
+                .append_query_results([vec![source_message.clone()]])
+                .append_query_results([vec![user_block]])
+                .into_connection();
+
+            let res =
+                service::create_message(&db, &mess.js, &cache, &limiter, &settings, req).await;
+
+            assert!(matches!(res, Err(AppError::Forbidden)));
+
+            Ok(())
+        }
     }
 }
 
+#[instrument(skip_all)]
 pub async fn get_messages(
     db: &DbConn,
+    cache: &CacheState,
     req: get_messages::Request,
 ) -> Result<get_messages::Response, AppError> {
-    let messages = repo::get_messages_by_ids(db, req.message_ids).await?;
+    let messages = cache
+        .messages
+        .get_or_load(
+            req.message_ids,
+            |it| it.message_id,
+            |message_ids| repo::get_messages_by_ids(db, message_ids),
+        )
+        .await?
+        .into_iter()
+        .filter(|it| it.hidden_at.is_none())
+        .collect::<Vec<_>>();
+
+    let message_ids: Vec<Uuid> = messages.iter().map(|it| it.message_id).collect();
+    let expand = req.expand;
+
+    // расширения грузятся параллельно, каждое одним запросом на весь набор
+    let (streams, messages_users, messages_topics) = tokio::try_join!(
+        async {
+            if !expand.streams {
+                return Ok(vec![]);
+            }
+
+            cache
+                .streams
+                .get_or_load(
+                    message_ids.clone(),
+                    |(stream, _)| stream.message_id,
+                    |message_ids| repo::get_streams_by_message_ids(db, message_ids),
+                )
+                .await
+        },
+        async {
+            if !expand.messages_users {
+                return Ok(vec![]);
+            }
+
+            repo::get_messages_users_by_message_ids(db, message_ids.clone()).await
+        },
+        async {
+            // у анонимного пользователя нет своих топиков
+            let Some(current_user) = req.current_user.filter(|_| expand.messages_topics) else {
+                return Ok(vec![]);
+            };
+
+            repo::get_messages_topics_by_message_ids_and_user_id(
+                db,
+                message_ids.clone(),
+                current_user.user_id,
+            )
+            .await
+        },
+    )?;
 
-    Ok(get_messages::Response { messages })
+    Ok(get_messages::Response {
+        messages,
+        streams,
+        messages_users,
+        messages_topics,
+    })
 }
 
 pub mod get_messages {
     use uuid::Uuid;
 
-    use crate::app::messages::repo::MessageModel;
+    use crate::app::{
+        current_user::CurrentUser,
+        messages::repo::{MessageModel, MessageTopicModel, MessageUserModel, StreamModel},
+    };
 
     pub struct Request {
+        pub current_user: Option<CurrentUser>,
         pub message_ids: Vec<Uuid>,
+        pub expand: Expansion,
+    }
+
+    #[derive(Default, Clone, Copy)]
+    pub struct Expansion {
+        pub streams: bool,
+        pub messages_users: bool,
+        pub messages_topics: bool,
     }
 
     pub struct Response {
         pub messages: Vec<MessageModel>,
+        pub streams: Vec<(StreamModel, Vec<MessageUserModel>)>,
+        pub messages_users: Vec<MessageUserModel>,
+        pub messages_topics: Vec<MessageTopicModel>,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bzd_lib::error::Error;
+        use sea_orm::{DatabaseBackend, MockDatabase};
+        use uuid::Uuid;
+
+        use crate::app::{
+            cache::{
+                CacheState,
+                settings::{CacheSettings, LruSettings},
+            },
+            current_user::CurrentUser,
+            messages::{
+                repo::{MessageModel, MessageTopicModel, MessageUserModel},
+                service::{
+                    self,
+                    get_messages::{Expansion, Request},
+                },
+            },
+        };
+
+        #[tokio::test]
+        async fn test_ok_get_messages_expanded() -> Result<(), Error> {
+            let lru = LruSettings {
+                capacity: 10,
+                ttl: 60,
+            };
+            let cache = CacheState::new(&CacheSettings {
+                messages: lru,
+                topics: lru,
+                streams: lru,
+            });
+
+            let message = MessageModel::new(Uuid::now_v7(), "TEXT".into(), "CODE".into());
+            let message_user = MessageUserModel::new(message.message_id, message.user_id, true);
+            let message_topic = MessageTopicModel::new(message.message_id, Uuid::now_v7());
+
+            let req = Request {
+                current_user: Some(CurrentUser {
+                    user_id: message.user_id,
+                }),
+                message_ids: vec![message.message_id],
+                expand: Expansion {
+                    streams: false,
+                    messages_users: true,
+                    messages_topics: true,
+                },
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![message.clone()]])
+                .append_query_results([vec![message_user.clone()]])
+                .append_query_results([vec![message_topic.clone()]])
+                .into_connection();
+
+            let res = service::get_messages(&db, &cache, req).await?;
+
+            assert_eq!(res.messages, vec![message]);
+            assert!(res.streams.is_empty());
+            assert_eq!(res.messages_users, vec![message_user]);
+            assert_eq!(res.messages_topics, vec![message_topic]);
+
+            Ok(())
+        }
     }
 }
 
+#[instrument(skip_all)]
 pub async fn get_message(
     db: &DbConn,
     req: get_message::Request,
 ) -> Result<get_message::Response, AppError> {
     let message = repo::get_message_by_id(db, req.message_id).await?;
 
+    if message.hidden_at.is_some() {
+        return Err(AppError::NotFound);
+    }
+
     Ok(get_message::Response { message })
 }
 
@@ -142,6 +481,7 @@ pub mod get_message {
     }
 }
 
+#[instrument(skip_all)]
 pub async fn get_message_messages(
     db: &DbConn,
     req: get_message_messages::Request,
@@ -150,96 +490,408 @@ pub async fn get_message_messages(
     let message = repo::get_message_by_id(db, req.message_id).await?;
     let stream = repo::find_stream_by_message_id(db, req.message_id).await?;
 
-    let limit = settings.limits.message;
+    let limit = page::limit(req.page_size, settings.limits.message);
+
+    let exclude_user_ids = match req.current_user {
+        Some(current_user) if req.exclude_blocked => {
+            repo::get_users_blocks_by_user_id(db, current_user.user_id)
+                .await?
+                .iter()
+                .map(|it| it.blocked_user_id)
+                .collect()
+        }
+        _ => vec![],
+    };
 
-    let mut messages = match stream {
+    let messages = match stream {
         Some(stream) => {
             repo::get_messages_by_stream_id(
                 db,
                 stream.stream_id,
-                req.cursor_message_id,
-                (limit + 1) as u64,
+                req.page_token,
+                exclude_user_ids,
+                limit + 1,
             )
             .await?
         }
-        None => vec![message],
-    };
-
-    let cursor_message = if messages.len() > limit as usize {
-        Some(messages.remove(0))
-    } else {
-        None
+        // скрытое сообщение без стрима отдается так же, как в get_message
+        None if message.hidden_at.is_some() => return Err(AppError::NotFound),
+        None => vec![message]
+            .into_iter()
+            .filter(|it| {
+                req.page_token
+                    .is_none_or(|token| token.admits(it.message_id))
+            })
+            .collect(),
     };
 
-    messages.reverse();
-
     Ok(get_message_messages::Response {
-        messages,
-        cursor_message,
+        messages: Page::new(messages, limit, req.page_token, |it| it.message_id),
     })
 }
 
 pub mod get_message_messages {
     use uuid::Uuid;
 
-    use crate::app::messages::repo::message;
+    use crate::app::{
+        current_user::CurrentUser,
+        messages::{
+            page::{Page, PageToken},
+            repo::message,
+        },
+    };
 
     pub struct Request {
+        pub current_user: Option<CurrentUser>,
         pub message_id: Uuid,
-        pub cursor_message_id: Option<Uuid>,
+        pub page_token: Option<PageToken>,
+        pub page_size: Option<u64>,
+        pub exclude_blocked: bool,
     }
 
     pub struct Response {
-        pub messages: Vec<message::Model>,
-        pub cursor_message: Option<message::Model>,
+        pub messages: Page<message::Model>,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bzd_lib::error::Error;
+        use chrono::Utc;
+        use sea_orm::{DatabaseBackend, MockDatabase, Value};
+        use uuid::Uuid;
+
+        use crate::app::{
+            current_user::CurrentUser,
+            error::AppError,
+            messages::{
+                repo::{MessageModel, StreamModel, UserBlockModel},
+                service::{self, get_message_messages::Request},
+                settings::MessagesSettings,
+            },
+        };
+
+        #[tokio::test]
+        async fn test_ok_get_message_messages_exclude_blocked() -> Result<(), Error> {
+            let settings = MessagesSettings::stub();
+            let now = Utc::now().naive_utc();
+
+            let message = MessageModel::new(Uuid::now_v7(), "TEXT".into(), "CODE".into());
+            let stream = StreamModel::new(message.message_id, message.text.clone());
+
+            let user_block = UserBlockModel {
+                user_block_id: Uuid::now_v7(),
+                user_id: Uuid::now_v7(),
+                blocked_user_id: Uuid::now_v7(),
+                created_at: now,
+                updated_at: now,
+            };
+
+            let req = Request {
+                current_user: Some(CurrentUser {
+                    user_id: user_block.user_id,
+                }),
+                message_id: message.message_id,
+                page_token: None,
+                page_size: None,
+                exclude_blocked: true,
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![message.clone()]])
+                .append_query_results([vec![stream.clone()]])
+                .append_query_results([vec![user_block.clone()]])
+                .append_query_results([vec![message.clone()]])
+                .into_connection();
+
+            let res = service::get_message_messages(&db, req, &settings).await?;
+
+            assert_eq!(res.messages.items, vec![message]);
+
+            let log = db.into_transaction_log();
+            let statement = log
+                .last()
+                .and_then(|it| it.statements().first().cloned())
+                .ok_or(AppError::Unreachable)?;
+
+            // ответы заблокированных авторов отсекаются в самом запросе стрима
+            assert!(statement.sql.contains("\"messages\".\"user_id\" NOT IN"));
+
+            let values = statement.values.map(|it| it.0).unwrap_or_default();
+            assert!(values.contains(&Value::from(user_block.blocked_user_id)));
+            assert!(values.contains(&Value::from(stream.stream_id)));
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_not_found_get_message_messages_hidden() -> Result<(), Error> {
+            let settings = MessagesSettings::stub();
+
+            let mut message = MessageModel::new(Uuid::now_v7(), "TEXT".into(), "CODE".into());
+            message.hidden_at = Some(Utc::now().naive_utc());
+
+            let req = Request {
+                current_user: None,
+                message_id: message.message_id,
+                page_token: None,
+                page_size: None,
+                exclude_blocked: false,
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![message]])
+                .append_query_results([Vec::<StreamModel>::new()])
+                .into_connection();
+
+            let res = service::get_message_messages(&db, req, &settings).await;
+
+            assert!(matches!(res, Err(AppError::NotFound)));
+
+            Ok(())
+        }
     }
 }
 
+#[instrument(skip_all)]
 pub async fn get_user_messages(
     db: &DbConn,
     req: get_user_messages::Request,
     settings: &MessagesSettings,
 ) -> Result<get_user_messages::Response, AppError> {
-    let limit = settings.limits.user;
+    let limit = page::limit(req.page_size, settings.limits.user);
 
-    let mut messages_users =
-        repo::get_messages_users_by_user_id(db, req.user_id, req.cursor_message_id, limit + 1)
-            .await?;
-
-    let cursor_message_user =
-        if messages_users.len() > usize::try_from(limit).map_err(|_| AppError::Unreachable)? {
-            messages_users.pop()
-        } else {
-            None
-        };
+    let messages_users =
+        repo::get_messages_users_by_user_id(db, req.user_id, req.page_token, limit + 1).await?;
 
     Ok(get_user_messages::Response {
-        messages_users,
-        cursor_message_user,
+        messages_users: Page::new(messages_users, limit, req.page_token, |it| {
+            it.message_user_id
+        }),
     })
 }
 
 pub mod get_user_messages {
     use uuid::Uuid;
 
-    use crate::app::messages::repo::MessageUserModel;
+    use crate::app::messages::{
+        page::{Page, PageToken},
+        repo::MessageUserModel,
+    };
 
     pub struct Request {
         pub user_id: Uuid,
-        pub cursor_message_id: Option<Uuid>,
+        pub page_token: Option<PageToken>,
+        pub page_size: Option<u64>,
     }
 
     pub struct Response {
-        pub messages_users: Vec<MessageUserModel>,
-        pub cursor_message_user: Option<MessageUserModel>,
+        pub messages_users: Page<MessageUserModel>,
     }
 }
 
+#[instrument(skip_all)]
+pub async fn get_topic_messages(
+    db: &DbConn,
+    req: get_topic_messages::Request,
+    settings: &MessagesSettings,
+) -> Result<get_topic_messages::Response, AppError> {
+    let topic = repo::get_topic_by_id(db, req.topic_id).await?;
+
+    let limit = page::limit(req.page_size, settings.limits.topic);
+
+    let exclude_user_ids = match req.current_user {
+        Some(current_user) if req.exclude_blocked => {
+            repo::get_users_blocks_by_user_id(db, current_user.user_id)
+                .await?
+                .iter()
+                .map(|it| it.blocked_user_id)
+                .collect()
+        }
+        _ => vec![],
+    };
+
+    let messages_topics = repo::get_messages_topics_by_topic_id(
+        db,
+        topic.topic_id,
+        req.page_token,
+        exclude_user_ids,
+        limit + 1,
+    )
+    .await?;
+
+    Ok(get_topic_messages::Response {
+        messages_topics: Page::new(messages_topics, limit, req.page_token, |it| it.message_id),
+    })
+}
+
+pub mod get_topic_messages {
+    use uuid::Uuid;
+
+    use crate::app::{
+        current_user::CurrentUser,
+        messages::{
+            page::{Page, PageToken},
+            repo::MessageTopicModel,
+        },
+    };
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+        pub topic_id: Uuid,
+        pub page_token: Option<PageToken>,
+        pub page_size: Option<u64>,
+        pub exclude_blocked: bool,
+    }
+
+    pub struct Response {
+        pub messages_topics: Page<MessageTopicModel>,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bzd_lib::error::Error;
+        use chrono::Utc;
+        use sea_orm::{DatabaseBackend, MockDatabase, Value};
+        use uuid::Uuid;
+
+        use crate::app::{
+            current_user::CurrentUser,
+            error::AppError,
+            messages::{
+                page::PageToken,
+                repo::{MessageTopicModel, TopicModel, UserBlockModel},
+                service::{self, get_topic_messages::Request},
+                settings::MessagesSettings,
+            },
+        };
+
+        #[tokio::test]
+        async fn test_ok_get_topic_messages() -> Result<(), Error> {
+            let settings = MessagesSettings::stub();
+            let topic = TopicModel::stub();
+
+            let messages_topics: Vec<MessageTopicModel> = (0..=settings.limits.topic)
+                .map(|_| MessageTopicModel::new(Uuid::now_v7(), topic.topic_id))
+                .rev()
+                .collect();
+
+            let req = Request {
+                current_user: None,
+                topic_id: topic.topic_id,
+                page_token: None,
+                page_size: None,
+                exclude_blocked: false,
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![topic.clone()]])
+                .append_query_results([messages_topics.clone()])
+                .into_connection();
+
+            let res = service::get_topic_messages(&db, req, &settings).await?;
+
+            assert_eq!(
+                res.messages_topics.items.len() as u64,
+                settings.limits.topic
+            );
+            assert_eq!(
+                res.messages_topics.older,
+                res.messages_topics
+                    .items
+                    .last()
+                    .map(|it| PageToken::older(it.message_id))
+            );
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_ok_get_topic_messages_exclude_blocked() -> Result<(), Error> {
+            let settings = MessagesSettings::stub();
+            let topic = TopicModel::stub();
+            let now = Utc::now().naive_utc();
+
+            let user_block = UserBlockModel {
+                user_block_id: Uuid::now_v7(),
+                user_id: Uuid::now_v7(),
+                blocked_user_id: Uuid::now_v7(),
+                created_at: now,
+                updated_at: now,
+            };
+
+            let req = Request {
+                current_user: Some(CurrentUser {
+                    user_id: user_block.user_id,
+                }),
+                topic_id: topic.topic_id,
+                page_token: None,
+                page_size: None,
+                exclude_blocked: true,
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![topic.clone()]])
+                .append_query_results([vec![user_block.clone()]])
+                .append_query_results([Vec::<MessageTopicModel>::new()])
+                .into_connection();
+
+            service::get_topic_messages(&db, req, &settings).await?;
+
+            let log = db.into_transaction_log();
+            let statement = log
+                .last()
+                .and_then(|it| it.statements().first().cloned())
+                .ok_or(AppError::Unreachable)?;
+
+            // скрытые сообщения и авторы из блок-листа не попадают в ленту топика
+            assert!(statement.sql.contains("\"messages\".\"hidden_at\" IS NULL"));
+            assert!(statement.sql.contains("\"messages\".\"user_id\" NOT IN"));
+
+            let values = statement.values.map(|it| it.0).unwrap_or_default();
+            assert!(values.contains(&Value::from(user_block.blocked_user_id)));
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_not_found_get_topic_messages() -> Result<(), Error> {
+            let settings = MessagesSettings::stub();
+
+            let req = Request {
+                current_user: None,
+                topic_id: Uuid::now_v7(),
+                page_token: None,
+                page_size: None,
+                exclude_blocked: false,
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([Vec::<TopicModel>::new()])
+                .into_connection();
+
+            let res = service::get_topic_messages(&db, req, &settings).await;
+
+            assert!(matches!(res, Err(AppError::NotFound)));
+
+            Ok(())
+        }
+    }
+}
+
+#[instrument(skip_all)]
 pub async fn get_streams(
     db: &DbConn,
+    cache: &CacheState,
     req: get_streams::Request,
 ) -> Result<get_streams::Response, AppError> {
-    let streams = repo::get_streams_by_message_ids(db, req.message_ids).await?;
+    let streams = cache
+        .streams
+        .get_or_load(
+            req.message_ids,
+            |(stream, _)| stream.message_id,
+            |message_ids| repo::get_streams_by_message_ids(db, message_ids),
+        )
+        .await?;
 
     Ok(get_streams::Response { streams })
 }
@@ -258,6 +910,7 @@ pub mod get_streams {
     }
 }
 
+#[instrument(skip_all)]
 pub async fn get_messages_users(
     db: &DbConn,
     req: get_messages_users::Request,
@@ -281,6 +934,7 @@ pub mod get_messages_users {
     }
 }
 
+#[instrument(skip_all)]
 pub async fn get_user_messages_topics(
     db: &DbConn,
     req: get_user_messages_topics::Request,
@@ -313,6 +967,7 @@ pub mod get_user_messages_topics {
     }
 }
 
+#[instrument(skip_all)]
 pub async fn create_message_topic(
     db: &DbConn,
     js: &Context,
@@ -334,6 +989,15 @@ pub async fn create_message_topic(
     )
     .await?;
 
+    audit::record(
+        &tx,
+        Some(current_user),
+        AuditLogAction::Created,
+        None,
+        Some(&message_topic),
+    )
+    .await?;
+
     tx.commit().await?;
 
     events::message_topic(js, &settings.events, &message_topic, Type::Created).await?;
@@ -359,6 +1023,7 @@ pub mod create_message_topic {
     }
 }
 
+#[instrument(skip_all)]
 pub async fn delete_message_topic(
     db: &DbConn,
     js: &Context,
@@ -371,7 +1036,20 @@ pub async fn delete_message_topic(
     let topic = repo::get_topic_by_id(db, message_topic.topic_id).await?;
     current_user.check_access(topic.user_id)?;
 
-    repo::delete_message_topic(db, message_topic.clone()).await?;
+    let tx = db.begin().await?;
+
+    repo::delete_message_topic(&tx, message_topic.clone()).await?;
+
+    audit::record(
+        &tx,
+        Some(current_user),
+        AuditLogAction::Deleted,
+        Some(&message_topic),
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
 
     events::message_topic(js, &settings.events, &message_topic, Type::Deleted).await?;
 
@@ -388,3 +1066,261 @@ pub mod delete_message_topic {
         pub message_topic_id: Uuid,
     }
 }
+
+#[instrument(skip_all)]
+pub async fn set_message_topics(
+    db: &DbConn,
+    js: &Context,
+    limiter: &Limiter,
+    settings: &MessagesSettings,
+    req: set_message_topics::Request,
+) -> Result<set_message_topics::Response, AppError> {
+    let changes = save_message_topics(db, limiter, settings, req).await?;
+
+    for message_topic in &changes.deleted {
+        events::message_topic(js, &settings.events, message_topic, Type::Deleted).await?;
+    }
+
+    for message_topic in &changes.created {
+        events::message_topic(js, &settings.events, message_topic, Type::Created).await?;
+    }
+
+    let mut messages_topics = changes.kept;
+    messages_topics.extend(changes.created);
+
+    Ok(set_message_topics::Response { messages_topics })
+}
+
+// Все проверки и запись без публикации, поэтому отказ в доступе тестируется без брокера
+async fn save_message_topics(
+    db: &DbConn,
+    limiter: &Limiter,
+    settings: &MessagesSettings,
+    req: set_message_topics::Request,
+) -> Result<set_message_topics::Changes, AppError> {
+    let current_user = req.current_user.ok_or(AppError::Forbidden)?;
+
+    let message = repo::get_message_by_id(db, req.message_id).await?;
+
+    let tx = db.begin().await?;
+
+    let topics = get_owned_topics(&tx, &current_user, req.topic_ids).await?;
+
+    let user_topic_ids = repo::get_topics_by_user_id(&tx, current_user.user_id)
+        .await?
+        .iter()
+        .map(|it| it.topic_id)
+        .collect();
+
+    let (messages_topics, deleted_messages_topics): (Vec<_>, Vec<_>) =
+        repo::get_messages_topics_by_message_ids_and_topics_ids(
+            &tx,
+            vec![message.message_id],
+            user_topic_ids,
+        )
+        .await?
+        .into_iter()
+        .partition(|it| topics.iter().any(|topic| topic.topic_id == it.topic_id));
+
+    for message_topic in &deleted_messages_topics {
+        repo::delete_message_topic(&tx, message_topic.clone()).await?;
+
+        audit::record(
+            &tx,
+            Some(current_user),
+            AuditLogAction::Deleted,
+            Some(message_topic),
+            None,
+        )
+        .await?;
+    }
+
+    let mut created_messages_topics = vec![];
+
+    for topic in topics.iter().filter(|topic| {
+        !messages_topics
+            .iter()
+            .any(|it| it.topic_id == topic.topic_id)
+    }) {
+        // списываются только новые связи, оставшиеся на месте бесплатны
+        limiter
+            .check(
+                Rpc::CreateMessageTopic,
+                &Some(current_user),
+                &settings.rate_limits.create_message_topic,
+            )
+            .await?;
+
+        let message_topic = repo::create_message_topic(
+            &tx,
+            MessageTopicModel::new(message.message_id, topic.topic_id),
+        )
+        .await?;
+
+        audit::record(
+            &tx,
+            Some(current_user),
+            AuditLogAction::Created,
+            None,
+            Some(&message_topic),
+        )
+        .await?;
+
+        created_messages_topics.push(message_topic);
+    }
+
+    tx.commit().await?;
+
+    Ok(set_message_topics::Changes {
+        kept: messages_topics,
+        created: created_messages_topics,
+        deleted: deleted_messages_topics,
+    })
+}
+
+pub mod set_message_topics {
+    use uuid::Uuid;
+
+    use crate::app::{current_user::CurrentUser, messages::repo::MessageTopicModel};
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+        pub message_id: Uuid,
+        pub topic_ids: Vec<Uuid>,
+    }
+
+    pub struct Response {
+        pub messages_topics: Vec<MessageTopicModel>,
+    }
+
+    pub struct Changes {
+        pub kept: Vec<MessageTopicModel>,
+        pub created: Vec<MessageTopicModel>,
+        pub deleted: Vec<MessageTopicModel>,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bzd_lib::error::Error;
+        use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+        use uuid::Uuid;
+
+        use crate::app::{
+            current_user::CurrentUser,
+            error::AppError,
+            limiter::Limiter,
+            messages::{
+                repo::{MessageModel, MessageTopicModel, TopicModel},
+                service::{self, set_message_topics::Request},
+                settings::MessagesSettings,
+            },
+        };
+
+        #[tokio::test]
+        async fn test_forbidden_set_message_topics() -> Result<(), Error> {
+            let message = MessageModel::new(Uuid::now_v7(), "TEXT".into(), "CODE".into());
+            let topic = TopicModel::stub();
+
+            let req = Request {
+                current_user: Some(CurrentUser {
+                    user_id: message.user_id,
+                }),
+                message_id: message.message_id,
+                topic_ids: vec![topic.topic_id],
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![message.clone()]])
+                .append_query_results([vec![topic.clone()]])
+                .into_connection();
+
+            let limiter = Limiter::Memory(Default::default());
+            let settings = MessagesSettings::stub();
+
+            let res = service::save_message_topics(&db, &limiter, &settings, req).await;
+
+            assert!(matches!(res, Err(AppError::Forbidden)));
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_rate_limited_set_message_topics() -> Result<(), Error> {
+            let limiter = Limiter::Memory(Default::default());
+            let settings = MessagesSettings::stub();
+            let capacity = settings.rate_limits.create_message_topic.capacity;
+
+            let message = MessageModel::new(Uuid::now_v7(), "TEXT".into(), "CODE".into());
+            let mut topics: Vec<TopicModel> = (0..=capacity)
+                .map(|_| TopicModel {
+                    user_id: message.user_id,
+                    ..TopicModel::stub()
+                })
+                .collect();
+            topics.sort_by_key(|it| it.topic_id);
+
+            let req = Request {
+                current_user: Some(CurrentUser {
+                    user_id: message.user_id,
+                }),
+                message_id: message.message_id,
+                topic_ids: topics.iter().map(|it| it.topic_id).collect(),
+            };
+
+            let mut db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![message.clone()]])
+                .append_query_results([topics.clone()])
+                .append_query_results([topics.clone()])
+                .append_query_results([Vec::<MessageTopicModel>::new()]);
+
+            // лимит пропускает ровно capacity новых связей
+            for topic in topics.iter().take(capacity as usize) {
+                db = db
+                    .append_exec_results([MockExecResult {
+                        last_insert_id: 0,
+                        rows_affected: 1,
+                    }])
+                    .append_query_results([vec![MessageTopicModel::new(
+                        message.message_id,
+                        topic.topic_id,
+                    )]])
+                    .append_exec_results([MockExecResult {
+                        last_insert_id: 0,
+                        rows_affected: 1,
+                    }]);
+            }
+
+            let res =
+                service::save_message_topics(&db.into_connection(), &limiter, &settings, req).await;
+
+            assert!(matches!(res, Err(AppError::RateLimited(_))));
+
+            Ok(())
+        }
+    }
+}
+
+async fn get_owned_topics<T: ConnectionTrait>(
+    db: &T,
+    current_user: &CurrentUser,
+    mut topic_ids: Vec<Uuid>,
+) -> Result<Vec<TopicModel>, AppError> {
+    if topic_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    topic_ids.sort();
+    topic_ids.dedup();
+
+    let topics = repo::get_topics_by_ids(db, topic_ids.clone()).await?;
+
+    if topics.len() != topic_ids.len() {
+        return Err(AppError::NotFound);
+    }
+
+    for topic in &topics {
+        current_user.check_access(topic.user_id)?;
+    }
+
+    Ok(topics)
+}
@@ -1,25 +1,91 @@
 use serde::Deserialize;
 
+use crate::app::{events::settings::EventsMode, limiter::settings::RateLimitSettings};
+
 #[derive(Deserialize, Clone)]
 pub struct MessagesSettings {
     // pub messages_limit: i64,
     pub limits: LimitsSettings,
 
+    pub rate_limits: RateLimitsSettings,
+
     pub events: EventsSettings,
 }
 
 #[derive(Deserialize, Clone)]
 pub struct EventsSettings {
+    pub messages: EventsMessagesSettings,
     pub messages_topics: EventsMessagesTopicsSettings,
+    pub streams: EventsStreamsSettings,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct EventsMessagesSettings {
+    pub subject: String,
+    #[serde(default)]
+    pub mode: EventsMode,
 }
 
 #[derive(Deserialize, Clone)]
 pub struct EventsMessagesTopicsSettings {
     pub subject: String,
+    #[serde(default)]
+    pub mode: EventsMode,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct EventsStreamsSettings {
+    pub subject: String,
+    #[serde(default)]
+    pub mode: EventsMode,
 }
 
 #[derive(Deserialize, Clone)]
 pub struct LimitsSettings {
     pub user: u64,
     pub message: u64,
+    pub topic: u64,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct RateLimitsSettings {
+    pub create_message: RateLimitSettings,
+    pub create_message_topic: RateLimitSettings,
+}
+
+#[cfg(test)]
+impl MessagesSettings {
+    pub fn stub() -> Self {
+        Self {
+            limits: LimitsSettings {
+                user: 2,
+                message: 2,
+                topic: 2,
+            },
+            rate_limits: RateLimitsSettings {
+                create_message: RateLimitSettings {
+                    capacity: 2,
+                    period: 1,
+                },
+                create_message_topic: RateLimitSettings {
+                    capacity: 2,
+                    period: 1,
+                },
+            },
+            events: EventsSettings {
+                messages: EventsMessagesSettings {
+                    subject: "SUBJECT".into(),
+                    mode: EventsMode::Binary,
+                },
+                messages_topics: EventsMessagesTopicsSettings {
+                    subject: "SUBJECT".into(),
+                    mode: EventsMode::Binary,
+                },
+                streams: EventsStreamsSettings {
+                    subject: "SUBJECT".into(),
+                    mode: EventsMode::Binary,
+                },
+            },
+        }
+    }
 }
@@ -1,8 +1,13 @@
-use crate::app::{db::DbState, mess::MessState, messages::settings::MessagesSettings};
+use crate::app::{
+    cache::CacheState, db::DbState, limiter::Limiter, mess::MessState,
+    messages::settings::MessagesSettings,
+};
 
 #[derive(Clone)]
 pub struct MessagesState {
     pub settings: MessagesSettings,
     pub db: DbState,
     pub mess: MessState,
+    pub limiter: Limiter,
+    pub cache: CacheState,
 }
@@ -0,0 +1,105 @@
+use std::time::Instant;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use bzd_lib::error::Error;
+use metrics::{counter, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use tonic::Code;
+
+use crate::app::{grpc, state::AppState};
+
+const BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+pub fn init() -> Result<PrometheusHandle, Error> {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("_seconds".into()), BUCKETS)?
+        .install_recorder()?;
+
+    Ok(handle)
+}
+
+pub async fn handler(State(state): State<AppState>) -> String {
+    state.metrics.render()
+}
+
+// Для unary ответов tonic кладет grpc-status в заголовки только при ошибке (trailers-only),
+// у успешных он в трейлерах, поэтому отсутствие заголовка считаем за OK
+pub async fn grpc(req: Request, next: Next) -> Response {
+    if !grpc::is_grpc(req.headers()) {
+        return next.run(req).await;
+    }
+
+    let method = req.uri().path().to_string();
+    let started_at = Instant::now();
+
+    let res = next.run(req).await;
+
+    let code = res
+        .headers()
+        .get("grpc-status")
+        .and_then(|it| it.to_str().ok())
+        .and_then(|it| it.parse::<i32>().ok())
+        .map_or(Code::Ok, Code::from);
+
+    // несуществующие методы не пишем как есть, чтобы не раздувать кардинальность
+    let method = match code {
+        Code::Unimplemented => "unknown".to_string(),
+        _ => method,
+    };
+
+    histogram!("grpc_server_handling_seconds", "method" => method.clone())
+        .record(started_at.elapsed().as_secs_f64());
+    counter!("grpc_server_handled_total", "method" => method, "code" => format!("{code:?}"))
+        .increment(1);
+
+    res
+}
+
+pub fn db_query(info: &sea_orm::metric::Info<'_>) {
+    let operation = info
+        .statement
+        .sql
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_uppercase();
+
+    histogram!(
+        "db_query_seconds",
+        "operation" => operation,
+        "failed" => info.failed.to_string(),
+    )
+    .record(info.elapsed.as_secs_f64());
+}
+
+// result: ok, duplicate (JetStream отбросил повтор) или error
+pub fn publish(subject: &str, result: &'static str) {
+    counter!(
+        "jetstream_publish_total",
+        "subject" => subject.to_string(),
+        "result" => result,
+    )
+    .increment(1);
+}
+
+pub fn message_created() {
+    counter!("messages_created_total").increment(1);
+}
+
+pub fn stream_created() {
+    counter!("streams_created_total").increment(1);
+}
+
+pub fn topic_user_created() {
+    counter!("topics_users_created_total").increment(1);
+}
+
+pub fn topic_user_deleted() {
+    counter!("topics_users_deleted_total").increment(1);
+}
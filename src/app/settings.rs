@@ -1,20 +1,47 @@
-use bzd_lib::settings::DBSettings;
 use bzd_lib::settings::NATSSettings;
 use bzd_lib::settings::Settings;
 
 use bzd_lib::settings::HttpSettings;
 use serde::Deserialize;
 
+use crate::app::admin;
+use crate::app::auth;
+use crate::app::cache;
+use crate::app::cors;
+use crate::app::db;
+use crate::app::health;
+use crate::app::limiter;
 use crate::app::messages;
+use crate::app::shutdown;
+use crate::app::telemetry;
 use crate::app::topics;
 
 #[derive(Deserialize, Clone)]
 pub struct AppSettings {
     pub http: HttpSettings,
-    pub db: DBSettings,
+    pub cors: cors::settings::CorsSettings,
+    pub health: health::settings::HealthSettings,
+    pub shutdown: shutdown::settings::ShutdownSettings,
+    pub telemetry: telemetry::settings::TelemetrySettings,
+    pub db: db::settings::DbSettings,
+    pub auth: auth::settings::AuthSettings,
     pub nats: NATSSettings,
+    pub cache: cache::settings::CacheSettings,
+    pub limiter: limiter::settings::LimiterSettings,
     pub messages: messages::settings::MessagesSettings,
     pub topics: topics::settings::TopicsSettings,
+    pub admin: admin::settings::AdminSettings,
 }
 
 impl Settings<AppSettings> for AppSettings {}
+
+// migrate, export и trends не поднимают сервер, поэтому auth и прочее им не нужно
+#[derive(Deserialize, Clone)]
+pub struct CommandSettings {
+    pub telemetry: telemetry::settings::TelemetrySettings,
+    pub db: db::settings::DbSettings,
+    pub topics: topics::settings::TopicsSettings,
+    pub admin: admin::settings::AdminSettings,
+}
+
+impl Settings<CommandSettings> for CommandSettings {}
@@ -0,0 +1,128 @@
+use std::{sync::Arc, time::Duration};
+
+use bzd_lib::error::Error;
+use tokio::{
+    signal::unix::{SignalKind, signal},
+    sync::watch,
+};
+use tracing::info;
+
+use crate::app::shutdown::settings::ShutdownSettings;
+
+pub mod settings;
+
+// Stopping: health уже отдает NOT_SERVING, но листенер еще принимает запросы,
+// Draining: новые запросы и эвенты не берем, дослушиваем активные
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Phase {
+    Running,
+    Stopping,
+    Draining,
+}
+
+#[derive(Clone)]
+pub struct Shutdown {
+    tx: Arc<watch::Sender<Phase>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self {
+            tx: Arc::new(watch::Sender::new(Phase::Running)),
+        }
+    }
+}
+
+impl Shutdown {
+    pub fn stop(&self) {
+        self.advance(Phase::Stopping);
+    }
+
+    pub fn trigger(&self) {
+        self.advance(Phase::Draining);
+    }
+
+    pub async fn stopping(&self) {
+        self.wait_for(Phase::Stopping).await
+    }
+
+    pub async fn wait(&self) {
+        self.wait_for(Phase::Draining).await
+    }
+
+    fn advance(&self, phase: Phase) {
+        self.tx.send_if_modified(|it| {
+            let modified = *it < phase;
+            *it = (*it).max(phase);
+            modified
+        });
+    }
+
+    async fn wait_for(&self, phase: Phase) {
+        let mut rx = self.tx.subscribe();
+        // Sender живет в self, поэтому ошибки закрытого канала тут не бывает
+        let _ = rx.wait_for(|it| *it >= phase).await;
+    }
+}
+
+pub async fn listen(shutdown: &Shutdown, settings: &ShutdownSettings) -> Result<(), Error> {
+    let mut sigterm = signal(SignalKind::terminate())?;
+
+    tokio::select! {
+        _ = sigterm.recv() => info!("shutdown: SIGTERM received"),
+        res = tokio::signal::ctrl_c() => {
+            res?;
+            info!("shutdown: SIGINT received");
+        },
+        _ = shutdown.wait() => return Ok(()),
+    }
+
+    // балансировщику нужно время заметить NOT_SERVING, пока листенер еще открыт
+    shutdown.stop();
+    tokio::time::sleep(Duration::from_secs(settings.pre_stop)).await;
+
+    shutdown.trigger();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::app::shutdown::Shutdown;
+
+    #[tokio::test]
+    async fn successfully_wait_trigger() {
+        let shutdown = Shutdown::default();
+
+        let waiter = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.wait().await }
+        });
+
+        shutdown.trigger();
+
+        let res = tokio::time::timeout(Duration::from_secs(1), waiter).await;
+        assert!(matches!(res, Ok(Ok(()))));
+    }
+
+    #[tokio::test]
+    async fn successfully_stop_before_drain() {
+        let shutdown = Shutdown::default();
+
+        shutdown.stop();
+
+        let stopping = tokio::time::timeout(Duration::from_secs(1), shutdown.stopping()).await;
+        assert!(stopping.is_ok());
+
+        let drain = tokio::time::timeout(Duration::from_millis(50), shutdown.wait()).await;
+        assert!(drain.is_err());
+
+        shutdown.trigger();
+        shutdown.stop();
+
+        let drain = tokio::time::timeout(Duration::from_secs(1), shutdown.wait()).await;
+        assert!(drain.is_ok());
+    }
+}
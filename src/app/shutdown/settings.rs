@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct ShutdownSettings {
+    // секунды между NOT_SERVING в health и закрытием листенера
+    pub pre_stop: u64,
+    // секунды на дослушивание активных запросов после закрытия листенера
+    pub timeout: u64,
+}
@@ -1,34 +1,132 @@
 use bzd_lib::error::Error;
+use metrics_exporter_prometheus::PrometheusHandle;
 
 use crate::app::{
-    db::DbState, mess::MessState, messages::state::MessagesState, settings::AppSettings,
-    topics::state::TopicsState,
+    admin::state::AdminState, auth::AuthInterceptor, cache::CacheState, db::DbState,
+    health::HealthState, limiter::Limiter, mess::MessState, messages::state::MessagesState,
+    metrics, settings::AppSettings, shutdown::Shutdown, topics::state::TopicsState,
+    users::state::UsersState,
 };
 
 #[derive(Clone)]
 pub struct AppState {
+    pub auth: AuthInterceptor,
+    pub metrics: PrometheusHandle,
+    pub health: HealthState,
+    pub shutdown: Shutdown,
+    pub cache: CacheState,
     pub topics: TopicsState,
     pub messages: MessagesState,
+    pub users: UsersState,
+    pub admin: AdminState,
 }
 
 impl AppState {
     pub async fn new(settings: AppSettings) -> Result<Self, Error> {
+        let metrics = metrics::init()?;
+
+        let auth = AuthInterceptor::new(&settings.auth)?;
+
         let db = DbState::new(&settings.db).await?;
 
         let mess = MessState::new(&settings.nats).await?;
 
+        let limiter = Limiter::new(&settings.limiter, &db);
+
+        let cache = CacheState::new(&settings.cache);
+
         let topics = TopicsState {
             settings: settings.topics.clone(),
             db: db.clone(),
             mess: mess.clone(),
+            limiter: limiter.clone(),
+            cache: cache.clone(),
         };
 
         let messages = MessagesState {
             settings: settings.messages.clone(),
             db: db.clone(),
             mess: mess.clone(),
+            limiter: limiter.clone(),
+            cache: cache.clone(),
+        };
+
+        let users = UsersState { db: db.clone() };
+
+        let admin = AdminState {
+            settings: settings.admin.clone(),
+            db: db.clone(),
+            mess: mess.clone(),
+            cache: cache.clone(),
+            messages_events: settings.messages.events.clone(),
+            topics_events: settings.topics.events.clone(),
         };
 
-        Ok(Self { topics, messages })
+        Ok(Self {
+            auth,
+            metrics,
+            health: HealthState::default(),
+            shutdown: Shutdown::default(),
+            cache,
+            topics,
+            messages,
+            users,
+            admin,
+        })
+    }
+}
+
+#[cfg(test)]
+impl AppState {
+    // пустой мок базы и ленивый NATS: годится для путей, которые отвечают до сервиса
+    pub async fn stub() -> Result<Self, Error> {
+        use sea_orm::{DatabaseBackend, MockDatabase};
+
+        use crate::app::{
+            admin::settings::AdminSettings, auth::settings::AuthSettings,
+            cache::settings::CacheSettings, messages::settings::MessagesSettings,
+            topics::settings::TopicsSettings,
+        };
+
+        let messages_settings = MessagesSettings::stub();
+        let topics_settings = TopicsSettings::stub();
+
+        let db = DbState::stub(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let mess = MessState::stub().await?;
+        let limiter = Limiter::Memory(Default::default());
+        let cache = CacheState::new(&CacheSettings::stub());
+
+        Ok(Self {
+            auth: AuthInterceptor::new(&AuthSettings::stub())?,
+            metrics: metrics_exporter_prometheus::PrometheusBuilder::new()
+                .build_recorder()
+                .handle(),
+            health: HealthState::default(),
+            shutdown: Shutdown::default(),
+            cache: cache.clone(),
+            topics: TopicsState {
+                settings: topics_settings.clone(),
+                db: db.clone(),
+                mess: mess.clone(),
+                limiter: limiter.clone(),
+                cache: cache.clone(),
+            },
+            messages: MessagesState {
+                settings: messages_settings.clone(),
+                db: db.clone(),
+                mess: mess.clone(),
+                limiter,
+                cache: cache.clone(),
+            },
+            users: UsersState { db: db.clone() },
+            admin: AdminState {
+                settings: AdminSettings::stub(),
+                db,
+                mess,
+                cache,
+                messages_events: messages_settings.events,
+                topics_events: topics_settings.events,
+            },
+        })
     }
 }
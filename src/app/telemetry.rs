@@ -0,0 +1,113 @@
+use axum::{extract::Request, http::HeaderMap, middleware::Next, response::Response};
+use bzd_lib::error::Error;
+use opentelemetry::{
+    Context, global,
+    propagation::{Extractor, Injector},
+    trace::TracerProvider as _,
+};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig as _};
+use opentelemetry_sdk::{Resource, propagation::TraceContextPropagator, trace::SdkTracerProvider};
+use tracing::{Instrument as _, Span, info_span};
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+use tracing_subscriber::{EnvFilter, layer::SubscriberExt as _, util::SubscriberInitExt as _};
+
+use crate::app::{grpc, telemetry::settings::TelemetrySettings};
+
+pub mod settings;
+
+pub fn init(settings: &TelemetrySettings) -> Result<Option<SdkTracerProvider>, Error> {
+    let Some(endpoint) = &settings.endpoint else {
+        bzd_lib::tracing::init()?;
+
+        return Ok(None);
+    };
+
+    let exporter = SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()?;
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(settings.service_name.clone())
+                .build(),
+        )
+        .build();
+
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    global::set_tracer_provider(provider.clone());
+
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("bzd-messages")))
+        .try_init()?;
+
+    Ok(Some(provider))
+}
+
+// traceparent из метаданных gRPC (это обычные http заголовки) становится родителем спана запроса
+pub async fn grpc(req: Request, next: Next) -> Response {
+    if !grpc::is_grpc(req.headers()) {
+        return next.run(req).await;
+    }
+
+    let cx = global::get_text_map_propagator(|it| it.extract(&HttpHeaders(req.headers())));
+
+    let span = info_span!(
+        "grpc",
+        otel.name = req.uri().path(),
+        otel.kind = "server",
+        rpc.system = "grpc",
+    );
+    let _ = span.set_parent(cx);
+
+    next.run(req).instrument(span).await
+}
+
+pub fn inject(headers: &mut async_nats::HeaderMap) {
+    let cx = Span::current().context();
+
+    global::get_text_map_propagator(|it| it.inject_context(&cx, &mut NatsHeaders(headers)));
+}
+
+pub fn extract(headers: Option<&async_nats::HeaderMap>) -> Context {
+    match headers {
+        Some(headers) => global::get_text_map_propagator(|it| it.extract(&NatsHeadersRef(headers))),
+        None => Context::new(),
+    }
+}
+
+struct HttpHeaders<'a>(&'a HeaderMap);
+
+impl Extractor for HttpHeaders<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|it| it.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|it| it.as_str()).collect()
+    }
+}
+
+struct NatsHeaders<'a>(&'a mut async_nats::HeaderMap);
+
+impl Injector for NatsHeaders<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.insert(key, value);
+    }
+}
+
+struct NatsHeadersRef<'a>(&'a async_nats::HeaderMap);
+
+impl Extractor for NatsHeadersRef<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(|it| it.as_str())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.iter().map(|(key, _)| key.as_ref()).collect()
+    }
+}
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct TelemetrySettings {
+    pub service_name: String,
+    // OTLP gRPC коллектор, без него спаны не экспортируются
+    pub endpoint: Option<String>,
+}
@@ -1,14 +1,27 @@
+use bzd_lib::{error::Error, settings::NATSSettings};
 use bzd_messages_api::topics::topics_service_server::TopicsServiceServer;
+use tonic::service::interceptor::InterceptedService;
 
-use crate::app::{state::AppState, topics::grpc::GrpcTopicsService};
+use crate::app::{auth::AuthInterceptor, state::AppState, topics::grpc::GrpcTopicsService};
 
-mod events;
+pub mod events;
 mod grpc;
+pub mod http;
 pub mod repo;
 mod service;
 pub mod settings;
 pub mod state;
+pub mod trends;
 
-pub fn topics_service(state: &AppState) -> TopicsServiceServer<GrpcTopicsService> {
-    TopicsServiceServer::new(GrpcTopicsService::new(state.topics.clone()))
+pub fn topics_service(
+    state: &AppState,
+) -> InterceptedService<TopicsServiceServer<GrpcTopicsService>, AuthInterceptor> {
+    TopicsServiceServer::with_interceptor(
+        GrpcTopicsService::new(state.topics.clone()),
+        state.auth.clone(),
+    )
+}
+
+pub async fn trends_consumer(state: &AppState, settings: &NATSSettings) -> Result<(), Error> {
+    trends::consumer(&state.topics, settings, &state.shutdown).await
 }
@@ -1,11 +1,13 @@
-use async_nats::{HeaderMap, jetstream::Context};
-use bytes::BytesMut;
+use async_nats::jetstream::Context;
 use bzd_messages_api::events::topic_user::Type;
-use prost::Message;
 
 use crate::app::{
     error::AppError,
-    topics::{repo::TopicUserModel, settings::EventsSettings},
+    events::{self, Event, Outgoing},
+    topics::{
+        repo::{TopicModel, TopicUserModel},
+        settings::EventsSettings,
+    },
 };
 
 pub async fn topic_user(
@@ -14,22 +16,74 @@ pub async fn topic_user(
     topic_user: &TopicUserModel,
     tp: Type,
 ) -> Result<(), AppError> {
-    let subject = settings.topics_users.subject.clone();
-    let mut buf = BytesMut::new();
-    let payload: bzd_messages_api::events::TopicUser = topic_user.into();
-    payload.encode(&mut buf)?;
+    events::publish(js, topic_user_event(settings, topic_user, tp)?).await
+}
+
+pub fn topic_user_event(
+    settings: &EventsSettings,
+    topic_user: &TopicUserModel,
+    tp: Type,
+) -> Result<Outgoing, AppError> {
+    let settings = &settings.topics_users;
+    let event = Event {
+        tp: tp.to_string(),
+        subject: topic_user.topic_user_id,
+        version: topic_user.updated_at,
+        data: bzd_messages_api::events::TopicUser::from(topic_user),
+    };
+
+    events::prepare(&settings.subject, settings.mode, event)
+}
+
+pub async fn topic(
+    js: &Context,
+    settings: &EventsSettings,
+    topic: &TopicModel,
+    tp: bzd_messages_api::events::topic::Type,
+) -> Result<(), AppError> {
+    events::publish(js, topic_event(settings, topic, tp)?).await
+}
 
-    let mut headers = HeaderMap::new();
-    headers.append("ce_type", tp.to_string());
+pub fn topic_event(
+    settings: &EventsSettings,
+    topic: &TopicModel,
+    tp: bzd_messages_api::events::topic::Type,
+) -> Result<Outgoing, AppError> {
+    let settings = &settings.topics;
+    let event = Event {
+        tp: tp.to_string(),
+        subject: topic.topic_id,
+        version: topic.updated_at,
+        data: bzd_messages_api::events::Topic::from(topic),
+    };
+
+    events::prepare(&settings.subject, settings.mode, event)
+}
 
-    js.publish_with_headers(subject, headers, buf.into())
-        .await?;
+mod topic {
+    use crate::app::{grpc::ToProtoTimestamp as _, topics::repo::TopicModel};
 
-    Ok(())
+    impl From<&TopicModel> for bzd_messages_api::events::Topic {
+        fn from(topic: &TopicModel) -> Self {
+            Self {
+                topic_id: Some(topic.topic_id.into()),
+                user_id: Some(topic.user_id.into()),
+                title: Some(topic.title.clone()),
+                code: Some(topic.code.clone()),
+                created_at: topic.created_at.to_option_proto(),
+                updated_at: topic.updated_at.to_option_proto(),
+            }
+        }
+    }
 }
 
 mod topic_user {
-    use crate::app::{grpc::ToProtoTimestamp as _, topics::repo::TopicUserModel};
+    use bzd_messages_api::events::Notifications;
+
+    use crate::app::{
+        grpc::ToProtoTimestamp as _,
+        topics::repo::{TopicUserModel, TopicUserNotifications},
+    };
 
     impl From<&TopicUserModel> for bzd_messages_api::events::TopicUser {
         fn from(topic_user: &TopicUserModel) -> Self {
@@ -37,9 +91,21 @@ mod topic_user {
                 topic_user_id: Some(topic_user.topic_user_id.into()),
                 user_id: Some(topic_user.user_id.into()),
                 topic_id: Some(topic_user.topic_id.into()),
+                notifications: Some(Notifications::from(topic_user.notifications).into()),
                 created_at: topic_user.created_at.to_option_proto(),
                 updated_at: topic_user.updated_at.to_option_proto(),
             }
         }
     }
+
+    impl From<TopicUserNotifications> for Notifications {
+        fn from(notifications: TopicUserNotifications) -> Self {
+            match notifications {
+                TopicUserNotifications::All => Self::All,
+                TopicUserNotifications::Roots => Self::Roots,
+                TopicUserNotifications::Muted => Self::Muted,
+                TopicUserNotifications::Digest => Self::Digest,
+            }
+        }
+    }
 }
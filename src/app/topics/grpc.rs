@@ -1,13 +1,24 @@
 use bzd_messages_api::topics::{
-    CreateTopicRequest, CreateTopicResponse, CreateTopicUserRequest, CreateTopicUserResponse,
-    DeleteTopicUserRequest, DeleteTopicUserResponse, GetEmojisRequest, GetEmojisResponse,
-    GetTopicRequest, GetTopicResponse, GetTopicsRequest, GetTopicsResponse, GetUserTopicsRequest,
-    GetUserTopicsResponse, GetUserTopicsUsersRequest, GetUserTopicsUsersResponse, Topic,
-    topics_service_server::TopicsService,
+    AcceptTopicTransferRequest, AcceptTopicTransferResponse, CreateTopicRequest,
+    CreateTopicResponse, CreateTopicUserRequest, CreateTopicUserResponse,
+    DeleteTopicTransferRequest, DeleteTopicTransferResponse, DeleteTopicUserRequest,
+    DeleteTopicUserResponse, GetEmojisRequest, GetEmojisResponse, GetTopicRequest,
+    GetTopicResponse, GetTopicsRequest, GetTopicsResponse, GetTrendingTopicsRequest,
+    GetTrendingTopicsResponse, GetUserTopicsRequest, GetUserTopicsResponse,
+    GetUserTopicsTransfersRequest, GetUserTopicsTransfersResponse, GetUserTopicsUsersRequest,
+    GetUserTopicsUsersResponse, Notifications, Topic, TransferTopicRequest, TransferTopicResponse,
+    UpdateTopicUserRequest, UpdateTopicUserResponse, topics_service_server::TopicsService,
 };
 use tonic::{Request, Response, Status};
 
-use crate::app::topics::{repo::TopicModel, state::TopicsState};
+use crate::app::{
+    auth,
+    error::AppError,
+    topics::{
+        repo::{TopicModel, TopicUserNotifications},
+        state::TopicsState,
+    },
+};
 
 pub struct GrpcTopicsService {
     pub state: TopicsState,
@@ -25,7 +36,7 @@ impl TopicsService for GrpcTopicsService {
         &self,
         req: Request<CreateTopicRequest>,
     ) -> Result<Response<CreateTopicResponse>, Status> {
-        let res = create_topic::handler(&self.state, req.into_inner()).await?;
+        let res = create_topic::handler(&self.state, auth::into_inner(req)).await?;
 
         Ok(Response::new(res))
     }
@@ -57,11 +68,20 @@ impl TopicsService for GrpcTopicsService {
         Ok(Response::new(res))
     }
 
+    async fn get_trending_topics(
+        &self,
+        _: Request<GetTrendingTopicsRequest>,
+    ) -> Result<Response<GetTrendingTopicsResponse>, Status> {
+        let res = get_trending_topics::handler(&self.state).await?;
+
+        Ok(Response::new(res))
+    }
+
     async fn get_user_topics_users(
         &self,
         req: Request<GetUserTopicsUsersRequest>,
     ) -> Result<Response<GetUserTopicsUsersResponse>, Status> {
-        let res = get_user_topics_users::handler(&self.state, req.into_inner()).await?;
+        let res = get_user_topics_users::handler(&self.state, auth::into_inner(req)).await?;
 
         Ok(Response::new(res))
     }
@@ -70,20 +90,65 @@ impl TopicsService for GrpcTopicsService {
         &self,
         req: Request<CreateTopicUserRequest>,
     ) -> Result<Response<CreateTopicUserResponse>, Status> {
-        let res = create_topic_user::handler(&self.state, req.into_inner()).await?;
+        let res = create_topic_user::handler(&self.state, auth::into_inner(req)).await?;
 
         Ok(Response::new(res))
     }
 
+    async fn update_topic_user(
+        &self,
+        req: Request<UpdateTopicUserRequest>,
+    ) -> Result<Response<UpdateTopicUserResponse>, Status> {
+        update_topic_user::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(UpdateTopicUserResponse::default()))
+    }
+
     async fn delete_topic_user(
         &self,
         req: Request<DeleteTopicUserRequest>,
     ) -> Result<Response<DeleteTopicUserResponse>, Status> {
-        delete_topic_user::handler(&self.state, req.into_inner()).await?;
+        delete_topic_user::handler(&self.state, auth::into_inner(req)).await?;
 
         Ok(Response::new(DeleteTopicUserResponse::default()))
     }
 
+    async fn transfer_topic(
+        &self,
+        req: Request<TransferTopicRequest>,
+    ) -> Result<Response<TransferTopicResponse>, Status> {
+        let res = transfer_topic::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn accept_topic_transfer(
+        &self,
+        req: Request<AcceptTopicTransferRequest>,
+    ) -> Result<Response<AcceptTopicTransferResponse>, Status> {
+        let res = accept_topic_transfer::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn delete_topic_transfer(
+        &self,
+        req: Request<DeleteTopicTransferRequest>,
+    ) -> Result<Response<DeleteTopicTransferResponse>, Status> {
+        delete_topic_transfer::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(DeleteTopicTransferResponse::default()))
+    }
+
+    async fn get_user_topics_transfers(
+        &self,
+        req: Request<GetUserTopicsTransfersRequest>,
+    ) -> Result<Response<GetUserTopicsTransfersResponse>, Status> {
+        let res = get_user_topics_transfers::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(res))
+    }
+
     async fn get_emojis(
         &self,
         _: Request<GetEmojisRequest>,
@@ -94,7 +159,7 @@ impl TopicsService for GrpcTopicsService {
     }
 }
 
-mod create_topic {
+pub mod create_topic {
     use bzd_messages_api::topics::{CreateTopicRequest, CreateTopicResponse};
 
     use crate::app::{
@@ -140,7 +205,7 @@ mod create_topic {
     }
 }
 
-mod get_topics {
+pub mod get_topics {
     use bzd_messages_api::topics::{GetTopicsRequest, GetTopicsResponse};
     use uuid::Uuid;
 
@@ -156,10 +221,10 @@ mod get_topics {
     };
 
     pub async fn handler(
-        TopicsState { db, .. }: &TopicsState,
+        TopicsState { db, cache, .. }: &TopicsState,
         req: GetTopicsRequest,
     ) -> Result<GetTopicsResponse, AppError> {
-        let res = service::get_topics(&db.conn, req.try_into()?).await?;
+        let res = service::get_topics(db.read_conn(), cache, req.try_into()?).await?;
 
         Ok(res.into())
     }
@@ -187,7 +252,7 @@ mod get_topics {
     }
 }
 
-mod get_topic {
+pub mod get_topic {
     use bzd_messages_api::topics::{GetTopicRequest, GetTopicResponse};
     use uuid::Uuid;
 
@@ -203,7 +268,7 @@ mod get_topic {
         TopicsState { db, .. }: &TopicsState,
         req: GetTopicRequest,
     ) -> Result<GetTopicResponse, AppError> {
-        let res = service::get_topic(&db.conn, req.try_into()?).await?;
+        let res = service::get_topic(db.read_conn(), req.try_into()?).await?;
 
         Ok(res.into())
     }
@@ -247,7 +312,7 @@ mod get_user_topics {
         TopicsState { db, .. }: &TopicsState,
         req: GetUserTopicsRequest,
     ) -> Result<GetUserTopicsResponse, AppError> {
-        let res = service::get_user_topics(&db.conn, req.try_into()?).await?;
+        let res = service::get_user_topics(db.read_conn(), req.try_into()?).await?;
 
         Ok(res.into())
     }
@@ -272,9 +337,39 @@ mod get_user_topics {
     }
 }
 
+pub mod get_trending_topics {
+    use bzd_messages_api::topics::GetTrendingTopicsResponse;
+
+    use crate::app::{
+        error::AppError,
+        topics::{
+            service::{self, get_trending_topics::Response},
+            state::TopicsState,
+        },
+    };
+
+    pub async fn handler(
+        TopicsState { db, settings, .. }: &TopicsState,
+    ) -> Result<GetTrendingTopicsResponse, AppError> {
+        let res = service::get_trending_topics(db.read_conn(), settings).await?;
+
+        Ok(res.into())
+    }
+
+    impl From<Response> for GetTrendingTopicsResponse {
+        fn from(res: Response) -> Self {
+            Self {
+                topic_ids: res.topics.iter().map(|it| it.topic_id.into()).collect(),
+                topics: res.topics.into_iter().map(Into::into).collect(),
+            }
+        }
+    }
+}
+
 mod get_user_topics_users {
     use bzd_messages_api::topics::{
-        GetUserTopicsUsersRequest, GetUserTopicsUsersResponse, get_user_topics_users_response,
+        GetUserTopicsUsersRequest, GetUserTopicsUsersResponse, Notifications,
+        get_user_topics_users_response,
     };
     use uuid::Uuid;
 
@@ -294,7 +389,7 @@ mod get_user_topics_users {
         TopicsState { db, .. }: &TopicsState,
         req: GetUserTopicsUsersRequest,
     ) -> Result<GetUserTopicsUsersResponse, AppError> {
-        let res = service::get_user_topics_users(&db.conn, req.try_into()?).await?;
+        let res = service::get_user_topics_users(db.read_conn(), req.try_into()?).await?;
 
         Ok(res.into())
     }
@@ -327,17 +422,19 @@ mod get_user_topics_users {
                 topic_user_id: Some(topic_user.topic_user_id.into()),
                 topic_id: Some(topic_user.topic_id.into()),
                 user_id: Some(topic_user.user_id.into()),
+                notifications: Some(Notifications::from(topic_user.notifications).into()),
             }
         }
     }
 }
 
-mod create_topic_user {
+pub mod create_topic_user {
     use bzd_messages_api::topics::{CreateTopicUserRequest, CreateTopicUserResponse};
 
     use crate::app::{
         current_user::CurrentUser,
         error::AppError,
+        limiter::Rpc,
         topics::{
             service::{
                 self,
@@ -349,12 +446,25 @@ mod create_topic_user {
 
     pub async fn handler(
         TopicsState {
-            db, mess, settings, ..
+            db,
+            mess,
+            settings,
+            limiter,
+            ..
         }: &TopicsState,
         req: CreateTopicUserRequest,
     ) -> Result<CreateTopicUserResponse, AppError> {
-        let res =
-            service::create_topic_user(&db.conn, &mess.js, &settings, req.try_into()?).await?;
+        let req: Request = req.try_into()?;
+
+        limiter
+            .check(
+                Rpc::CreateTopicUser,
+                &req.current_user,
+                &settings.rate_limits.create_topic_user,
+            )
+            .await?;
+
+        let res = service::create_topic_user(&db.conn, &mess.js, settings, req).await?;
 
         Ok(res.into())
     }
@@ -379,7 +489,43 @@ mod create_topic_user {
     }
 }
 
-mod delete_topic_user {
+mod update_topic_user {
+    use bzd_messages_api::topics::UpdateTopicUserRequest;
+
+    use crate::app::{
+        current_user::CurrentUser,
+        error::AppError,
+        topics::{
+            service::{self, update_topic_user::Request},
+            state::TopicsState,
+        },
+    };
+
+    pub async fn handler(
+        TopicsState {
+            db, mess, settings, ..
+        }: &TopicsState,
+        req: UpdateTopicUserRequest,
+    ) -> Result<(), AppError> {
+        service::update_topic_user(&db.conn, &mess.js, settings, req.try_into()?).await?;
+
+        Ok(())
+    }
+
+    impl TryFrom<UpdateTopicUserRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: UpdateTopicUserRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                topic_user_id: req.topic_user_id().parse()?,
+                notifications: req.notifications().try_into()?,
+            })
+        }
+    }
+}
+
+pub mod delete_topic_user {
     use bzd_messages_api::topics::DeleteTopicUserRequest;
 
     use crate::app::{
@@ -397,7 +543,7 @@ mod delete_topic_user {
         }: &TopicsState,
         req: DeleteTopicUserRequest,
     ) -> Result<(), AppError> {
-        service::delete_topic_user(&db.conn, &mess.js, &settings, req.try_into()?).await?;
+        service::delete_topic_user(&db.conn, &mess.js, settings, req.try_into()?).await?;
 
         Ok(())
     }
@@ -414,6 +560,194 @@ mod delete_topic_user {
     }
 }
 
+mod transfer_topic {
+    use bzd_messages_api::topics::{TransferTopicRequest, TransferTopicResponse};
+
+    use crate::app::{
+        current_user::CurrentUser,
+        error::AppError,
+        topics::{
+            service::{
+                self,
+                transfer_topic::{Request, Response},
+            },
+            state::TopicsState,
+        },
+    };
+
+    pub async fn handler(
+        TopicsState { db, .. }: &TopicsState,
+        req: TransferTopicRequest,
+    ) -> Result<TransferTopicResponse, AppError> {
+        let res = service::transfer_topic(&db.conn, req.try_into()?).await?;
+
+        Ok(res.into())
+    }
+
+    impl TryFrom<TransferTopicRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: TransferTopicRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                topic_id: req.topic_id().parse()?,
+                user_id: req.user_id().parse()?,
+            })
+        }
+    }
+
+    impl From<Response> for TransferTopicResponse {
+        fn from(res: Response) -> Self {
+            Self {
+                topic_transfer_id: Some(res.topic_transfer.topic_transfer_id.into()),
+            }
+        }
+    }
+}
+
+mod accept_topic_transfer {
+    use bzd_messages_api::topics::{AcceptTopicTransferRequest, AcceptTopicTransferResponse};
+
+    use crate::app::{
+        current_user::CurrentUser,
+        error::AppError,
+        topics::{
+            service::{
+                self,
+                accept_topic_transfer::{Request, Response},
+            },
+            state::TopicsState,
+        },
+    };
+
+    pub async fn handler(
+        TopicsState {
+            db,
+            mess,
+            settings,
+            cache,
+            ..
+        }: &TopicsState,
+        req: AcceptTopicTransferRequest,
+    ) -> Result<AcceptTopicTransferResponse, AppError> {
+        let res =
+            service::accept_topic_transfer(&db.conn, &mess.js, cache, settings, req.try_into()?)
+                .await?;
+
+        Ok(res.into())
+    }
+
+    impl TryFrom<AcceptTopicTransferRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: AcceptTopicTransferRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                topic_transfer_id: req.topic_transfer_id().parse()?,
+            })
+        }
+    }
+
+    impl From<Response> for AcceptTopicTransferResponse {
+        fn from(res: Response) -> Self {
+            Self {
+                topic: Some(res.topic.into()),
+            }
+        }
+    }
+}
+
+mod delete_topic_transfer {
+    use bzd_messages_api::topics::DeleteTopicTransferRequest;
+
+    use crate::app::{
+        current_user::CurrentUser,
+        error::AppError,
+        topics::{
+            service::{self, delete_topic_transfer::Request},
+            state::TopicsState,
+        },
+    };
+
+    pub async fn handler(
+        TopicsState { db, .. }: &TopicsState,
+        req: DeleteTopicTransferRequest,
+    ) -> Result<(), AppError> {
+        service::delete_topic_transfer(&db.conn, req.try_into()?).await?;
+
+        Ok(())
+    }
+
+    impl TryFrom<DeleteTopicTransferRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: DeleteTopicTransferRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                topic_transfer_id: req.topic_transfer_id().parse()?,
+            })
+        }
+    }
+}
+
+mod get_user_topics_transfers {
+    use bzd_messages_api::topics::{
+        GetUserTopicsTransfersRequest, GetUserTopicsTransfersResponse,
+        get_user_topics_transfers_response,
+    };
+
+    use crate::app::{
+        current_user::CurrentUser,
+        error::AppError,
+        topics::{
+            repo::TopicTransferModel,
+            service::{
+                self,
+                get_user_topics_transfers::{Request, Response},
+            },
+            state::TopicsState,
+        },
+    };
+
+    pub async fn handler(
+        TopicsState { db, .. }: &TopicsState,
+        req: GetUserTopicsTransfersRequest,
+    ) -> Result<GetUserTopicsTransfersResponse, AppError> {
+        let res = service::get_user_topics_transfers(db.read_conn(), req.try_into()?).await?;
+
+        Ok(res.into())
+    }
+
+    impl TryFrom<GetUserTopicsTransfersRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: GetUserTopicsTransfersRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+            })
+        }
+    }
+
+    impl From<Response> for GetUserTopicsTransfersResponse {
+        fn from(res: Response) -> Self {
+            Self {
+                topics_transfers: res.topics_transfers.iter().map(Into::into).collect(),
+            }
+        }
+    }
+
+    impl From<&TopicTransferModel> for get_user_topics_transfers_response::TopicTransfer {
+        fn from(topic_transfer: &TopicTransferModel) -> Self {
+            Self {
+                topic_transfer_id: Some(topic_transfer.topic_transfer_id.into()),
+                topic_id: Some(topic_transfer.topic_id.into()),
+                user_id: Some(topic_transfer.user_id.into()),
+                to_user_id: Some(topic_transfer.to_user_id.into()),
+            }
+        }
+    }
+}
+
 mod get_emojis {
     use bzd_messages_api::topics::{GetEmojisResponse, get_emojis_response::Emoji};
 
@@ -428,7 +762,7 @@ mod get_emojis {
     pub async fn handler(
         TopicsState { settings, .. }: &TopicsState,
     ) -> Result<GetEmojisResponse, AppError> {
-        let res = service::get_emojis(&settings)?;
+        let res = service::get_emojis(settings)?;
 
         Ok(res.into())
     }
@@ -458,3 +792,28 @@ impl From<TopicModel> for Topic {
         }
     }
 }
+
+impl From<TopicUserNotifications> for Notifications {
+    fn from(notifications: TopicUserNotifications) -> Self {
+        match notifications {
+            TopicUserNotifications::All => Self::All,
+            TopicUserNotifications::Roots => Self::Roots,
+            TopicUserNotifications::Muted => Self::Muted,
+            TopicUserNotifications::Digest => Self::Digest,
+        }
+    }
+}
+
+impl TryFrom<Notifications> for TopicUserNotifications {
+    type Error = AppError;
+
+    fn try_from(notifications: Notifications) -> Result<Self, Self::Error> {
+        match notifications {
+            Notifications::All => Ok(Self::All),
+            Notifications::Roots => Ok(Self::Roots),
+            Notifications::Muted => Ok(Self::Muted),
+            Notifications::Digest => Ok(Self::Digest),
+            Notifications::Unspecified => Err(AppError::Validation),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get, post},
+};
+use bzd_messages_api::topics::{
+    CreateTopicRequest, CreateTopicResponse, CreateTopicUserRequest, CreateTopicUserResponse,
+    DeleteTopicUserRequest, GetTopicRequest, GetTopicResponse, GetTopicsRequest, GetTopicsResponse,
+    GetTrendingTopicsResponse,
+};
+use serde::Deserialize;
+
+use crate::app::{auth, error::AppError, state::AppState, topics::grpc};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/topics", get(get_topics).post(create_topic))
+        .route("/topics/trending", get(get_trending_topics))
+        .route("/topics/{topic_id}", get(get_topic))
+        .route("/topics/{topic_id}/users", post(create_topic_user))
+        .route("/topics-users/{topic_user_id}", delete(delete_topic_user))
+}
+
+#[derive(Deserialize)]
+struct IdsQuery {
+    // через запятую: ?topic_ids=a,b,c
+    topic_ids: String,
+}
+
+async fn create_topic(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateTopicRequest>,
+) -> Result<Json<CreateTopicResponse>, AppError> {
+    let req = auth::apply(state.auth.authenticate(&headers)?, req);
+    let res = grpc::create_topic::handler(&state.topics, req).await?;
+
+    Ok(Json(res))
+}
+
+async fn get_topics(
+    State(state): State<AppState>,
+    Query(query): Query<IdsQuery>,
+) -> Result<Json<GetTopicsResponse>, AppError> {
+    let req = GetTopicsRequest {
+        topic_ids: query
+            .topic_ids
+            .split(',')
+            .filter(|it| !it.is_empty())
+            .map(Into::into)
+            .collect(),
+    };
+    let res = grpc::get_topics::handler(&state.topics, req).await?;
+
+    Ok(Json(res))
+}
+
+async fn get_topic(
+    State(state): State<AppState>,
+    Path(topic_id): Path<String>,
+) -> Result<Json<GetTopicResponse>, AppError> {
+    let req = GetTopicRequest {
+        topic_id: Some(topic_id),
+    };
+    let res = grpc::get_topic::handler(&state.topics, req).await?;
+
+    Ok(Json(res))
+}
+
+async fn get_trending_topics(
+    State(state): State<AppState>,
+) -> Result<Json<GetTrendingTopicsResponse>, AppError> {
+    let res = grpc::get_trending_topics::handler(&state.topics).await?;
+
+    Ok(Json(res))
+}
+
+async fn create_topic_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(topic_id): Path<String>,
+) -> Result<Json<CreateTopicUserResponse>, AppError> {
+    let req = CreateTopicUserRequest {
+        current_user_id: None,
+        topic_id: Some(topic_id),
+    };
+    let req = auth::apply(state.auth.authenticate(&headers)?, req);
+    let res = grpc::create_topic_user::handler(&state.topics, req).await?;
+
+    Ok(Json(res))
+}
+
+async fn delete_topic_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(topic_user_id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let req = DeleteTopicUserRequest {
+        current_user_id: None,
+        topic_user_id: Some(topic_user_id),
+    };
+    let req = auth::apply(state.auth.authenticate(&headers)?, req);
+    grpc::delete_topic_user::handler(&state.topics, req).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,17 +1,28 @@
+use chrono::{NaiveDateTime, Utc};
 use sea_orm::{
-    ColumnTrait as _, ConnectionTrait, EntityTrait as _, IntoActiveModel as _, ModelTrait,
-    QueryFilter as _, sea_query::OnConflict,
+    ActiveModelTrait as _, ActiveValue::Set, ColumnTrait as _, ConnectionTrait, DbBackend,
+    EntityTrait as _, IntoActiveModel as _, JoinType, ModelTrait, QueryFilter as _,
+    QueryOrder as _, QuerySelect as _, Statement, prelude::Expr, sea_query::OnConflict,
 };
+use tracing::instrument;
 use uuid::Uuid;
 
 use crate::app::error::AppError;
 
-mod topic;
-mod topic_user;
+pub mod topic;
+pub mod topic_transfer;
+pub mod topic_trend;
+pub mod topic_user;
+mod user_block;
 
 pub type TopicModel = topic::Model;
+pub type TopicTransferModel = topic_transfer::Model;
+pub type TopicTrendModel = topic_trend::Model;
 pub type TopicUserModel = topic_user::Model;
+pub type TopicUserNotifications = topic_user::Notifications;
+pub type UserBlockModel = user_block::Model;
 
+#[instrument(skip_all)]
 pub async fn create_topic<T: ConnectionTrait>(
     db: &T,
     model: TopicModel,
@@ -39,6 +50,7 @@ pub async fn create_topic<T: ConnectionTrait>(
     Ok(topic)
 }
 
+#[instrument(skip_all)]
 pub async fn get_topics_by_user_id<T: ConnectionTrait>(
     db: &T,
     user_id: Uuid,
@@ -51,6 +63,7 @@ pub async fn get_topics_by_user_id<T: ConnectionTrait>(
     Ok(topics)
 }
 
+#[instrument(skip_all)]
 pub async fn get_topics_by_ids<T: ConnectionTrait>(
     db: &T,
     topic_ids: Vec<Uuid>,
@@ -63,6 +76,7 @@ pub async fn get_topics_by_ids<T: ConnectionTrait>(
     Ok(topics)
 }
 
+#[instrument(skip_all)]
 pub async fn get_topics_users_by_user_id<T: ConnectionTrait>(
     db: &T,
     user_id: Uuid,
@@ -75,6 +89,7 @@ pub async fn get_topics_users_by_user_id<T: ConnectionTrait>(
     Ok(topics)
 }
 
+#[instrument(skip_all)]
 pub async fn get_topic_by_id<T: ConnectionTrait>(
     db: &T,
     topic_id: Uuid,
@@ -87,6 +102,7 @@ pub async fn get_topic_by_id<T: ConnectionTrait>(
     Ok(topic)
 }
 
+#[instrument(skip_all)]
 pub async fn get_topic_user_by_id<T: ConnectionTrait>(
     db: &T,
     topic_user_id: Uuid,
@@ -99,6 +115,22 @@ pub async fn get_topic_user_by_id<T: ConnectionTrait>(
     Ok(topic_user)
 }
 
+#[instrument(skip_all)]
+pub async fn find_user_block<T: ConnectionTrait>(
+    db: &T,
+    user_id: Uuid,
+    blocked_user_id: Uuid,
+) -> Result<Option<UserBlockModel>, AppError> {
+    let user_block = user_block::Entity::find()
+        .filter(user_block::Column::UserId.eq(user_id))
+        .filter(user_block::Column::BlockedUserId.eq(blocked_user_id))
+        .one(db)
+        .await?;
+
+    Ok(user_block)
+}
+
+#[instrument(skip_all)]
 pub async fn create_topic_user<T: ConnectionTrait>(
     db: &T,
     model: TopicUserModel,
@@ -123,6 +155,22 @@ pub async fn create_topic_user<T: ConnectionTrait>(
     Ok(topic_user)
 }
 
+#[instrument(skip_all)]
+pub async fn update_topic_user<T: ConnectionTrait>(
+    db: &T,
+    topic_user: TopicUserModel,
+    notifications: TopicUserNotifications,
+) -> Result<TopicUserModel, AppError> {
+    let mut topic_user = topic_user.into_active_model();
+    topic_user.notifications = Set(notifications);
+    topic_user.updated_at = Set(Utc::now().naive_utc());
+
+    let topic_user = topic_user.update(db).await?;
+
+    Ok(topic_user)
+}
+
+#[instrument(skip_all)]
 pub async fn delete_topic_user<T: ConnectionTrait>(
     db: &T,
     topic_user: TopicUserModel,
@@ -131,3 +179,209 @@ pub async fn delete_topic_user<T: ConnectionTrait>(
 
     Ok(())
 }
+
+#[instrument(skip_all)]
+pub async fn upsert_topic_trend<T: ConnectionTrait>(
+    db: &T,
+    model: TopicTrendModel,
+) -> Result<(), AppError> {
+    topic_trend::Entity::insert(model.into_active_model())
+        .on_conflict(
+            OnConflict::column(topic_trend::Column::TopicId)
+                .value(
+                    topic_trend::Column::Score,
+                    Expr::cust(
+                        "GREATEST(topics_trends.score, excluded.score) \
+                        + LN(1 + EXP(-ABS(topics_trends.score - excluded.score)))",
+                    ),
+                )
+                .value(
+                    topic_trend::Column::ActivityAt,
+                    Expr::cust("GREATEST(topics_trends.activity_at, excluded.activity_at)"),
+                )
+                .update_column(topic_trend::Column::UpdatedAt)
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+// Та же формула, что в TopicTrendModel::new и upsert_topic_trend, только сразу по всем строкам окна:
+// log-sum-exp через максимум по топику, чтобы EXP не переполнялся
+const BACKFILL_TOPICS_TRENDS: &str = r#"
+WITH activity AS (
+    SELECT topic_id, created_at, LN($1) + EXTRACT(EPOCH FROM created_at) / $3 AS score
+    FROM messages_topics WHERE created_at >= $4
+    UNION ALL
+    SELECT topic_id, created_at, LN($2) + EXTRACT(EPOCH FROM created_at) / $3 AS score
+    FROM topics_users WHERE created_at >= $4
+), scored AS (
+    SELECT topic_id, created_at, score, MAX(score) OVER (PARTITION BY topic_id) AS max_score
+    FROM activity
+)
+INSERT INTO topics_trends (topic_id, score, activity_at, created_at, updated_at)
+SELECT topic_id, MAX(max_score) + LN(SUM(EXP(score - max_score))), MAX(created_at), $5, $5
+FROM scored GROUP BY topic_id
+ON CONFLICT (topic_id) DO UPDATE
+SET score = excluded.score, activity_at = excluded.activity_at, updated_at = excluded.updated_at
+"#;
+
+// Очки пересчитываются с нуля, а не складываются с накопленными, поэтому повторный запуск безопасен
+#[instrument(skip_all)]
+pub async fn backfill_topics_trends<T: ConnectionTrait>(
+    db: &T,
+    message_topic: f64,
+    topic_user: f64,
+    decay: u64,
+    activity_from: NaiveDateTime,
+) -> Result<u64, AppError> {
+    let res = db
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            BACKFILL_TOPICS_TRENDS,
+            [
+                message_topic.into(),
+                topic_user.into(),
+                (decay as f64).into(),
+                activity_from.into(),
+                Utc::now().naive_utc().into(),
+            ],
+        ))
+        .await?;
+
+    Ok(res.rows_affected())
+}
+
+#[instrument(skip_all)]
+pub async fn get_trending_topics<T: ConnectionTrait>(
+    db: &T,
+    activity_from: NaiveDateTime,
+    limit: u64,
+) -> Result<Vec<TopicModel>, AppError> {
+    let topics = topic::Entity::find()
+        .join(
+            JoinType::InnerJoin,
+            topic::Entity::belongs_to(topic_trend::Entity)
+                .to(topic_trend::Column::TopicId)
+                .from(topic::Column::TopicId)
+                .into(),
+        )
+        .filter(topic_trend::Column::ActivityAt.gte(activity_from))
+        .order_by_desc(topic_trend::Column::Score)
+        .limit(limit)
+        .all(db)
+        .await?;
+
+    Ok(topics)
+}
+
+#[instrument(skip_all)]
+pub async fn find_topic_by_code_and_user_id<T: ConnectionTrait>(
+    db: &T,
+    code: String,
+    user_id: Uuid,
+) -> Result<Option<TopicModel>, AppError> {
+    let topic = topic::Entity::find()
+        .filter(topic::Column::Code.eq(code))
+        .filter(topic::Column::UserId.eq(user_id))
+        .one(db)
+        .await?;
+
+    Ok(topic)
+}
+
+#[instrument(skip_all)]
+pub async fn update_topic_user_id<T: ConnectionTrait>(
+    db: &T,
+    topic: TopicModel,
+    user_id: Uuid,
+) -> Result<TopicModel, AppError> {
+    let mut topic = topic.into_active_model();
+    topic.user_id = Set(user_id);
+    topic.updated_at = Set(Utc::now().naive_utc());
+
+    let topic = topic.update(db).await?;
+
+    Ok(topic)
+}
+
+#[instrument(skip_all)]
+pub async fn find_topic_user<T: ConnectionTrait>(
+    db: &T,
+    topic_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<TopicUserModel>, AppError> {
+    let topic_user = topic_user::Entity::find()
+        .filter(topic_user::Column::TopicId.eq(topic_id))
+        .filter(topic_user::Column::UserId.eq(user_id))
+        .one(db)
+        .await?;
+
+    Ok(topic_user)
+}
+
+#[instrument(skip_all)]
+pub async fn create_topic_transfer<T: ConnectionTrait>(
+    db: &T,
+    model: TopicTransferModel,
+) -> Result<TopicTransferModel, AppError> {
+    topic_transfer::Entity::insert(model.clone().into_active_model())
+        .on_conflict(
+            OnConflict::column(topic_transfer::Column::TopicId)
+                .update_columns([
+                    topic_transfer::Column::TopicTransferId,
+                    topic_transfer::Column::UserId,
+                    topic_transfer::Column::ToUserId,
+                    topic_transfer::Column::UpdatedAt,
+                ])
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+
+    let topic_transfer = topic_transfer::Entity::find()
+        .filter(topic_transfer::Column::TopicId.eq(model.topic_id))
+        .one(db)
+        .await?
+        .ok_or(AppError::Unreachable)?;
+
+    Ok(topic_transfer)
+}
+
+#[instrument(skip_all)]
+pub async fn get_topic_transfer_by_id<T: ConnectionTrait>(
+    db: &T,
+    topic_transfer_id: Uuid,
+) -> Result<TopicTransferModel, AppError> {
+    let topic_transfer = topic_transfer::Entity::find_by_id(topic_transfer_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    Ok(topic_transfer)
+}
+
+#[instrument(skip_all)]
+pub async fn get_topics_transfers_by_to_user_id<T: ConnectionTrait>(
+    db: &T,
+    to_user_id: Uuid,
+) -> Result<Vec<TopicTransferModel>, AppError> {
+    let topics_transfers = topic_transfer::Entity::find()
+        .filter(topic_transfer::Column::ToUserId.eq(to_user_id))
+        .all(db)
+        .await?;
+
+    Ok(topics_transfers)
+}
+
+#[instrument(skip_all)]
+pub async fn delete_topic_transfer<T: ConnectionTrait>(
+    db: &T,
+    topic_transfer: TopicTransferModel,
+) -> Result<(), AppError> {
+    topic_transfer.delete(db).await?;
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use chrono::Utc;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "topics_trends")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub topic_id: Uuid,
+    pub score: f64,
+    pub activity_at: DateTime,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl Model {
+    // Очки хранятся в лог-пространстве с прямым затуханием: ln(weight) + t / decay,
+    // поэтому сортировка по score сразу дает порядок по затухшим на текущий момент очкам
+    pub fn new(topic_id: Uuid, activity_at: DateTime, weight: f64, decay: u64) -> Self {
+        let now = Utc::now().naive_utc();
+        let score = weight.ln() + activity_at.and_utc().timestamp() as f64 / decay as f64;
+
+        Self {
+            topic_id,
+            score,
+            activity_at,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use uuid::Uuid;
+
+    use crate::app::topics::repo::TopicTrendModel;
+
+    #[test]
+    fn newer_activity_outranks_older() {
+        let now = Utc::now().naive_utc();
+
+        let old = TopicTrendModel::new(Uuid::now_v7(), now - Duration::days(2), 1.0, 86400);
+        let new = TopicTrendModel::new(Uuid::now_v7(), now, 1.0, 86400);
+
+        assert!(new.score > old.score);
+    }
+
+    #[test]
+    fn weight_is_decayed_over_time() {
+        let now = Utc::now().naive_utc();
+
+        let old = TopicTrendModel::new(Uuid::now_v7(), now - Duration::days(1), 3.0, 86400);
+        let new = TopicTrendModel::new(Uuid::now_v7(), now, 1.0, 86400);
+
+        assert!(old.score > new.score);
+        assert!((old.score - new.score - (3.0_f64.ln() - 1.0)).abs() < 1e-6);
+    }
+}
@@ -1,13 +1,15 @@
 use chrono::Utc;
 use sea_orm::entity::prelude::*;
+use serde::Serialize;
 
-#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize)]
 #[sea_orm(table_name = "topics_users")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub topic_user_id: Uuid,
     pub user_id: Uuid,
     pub topic_id: Uuid,
+    pub notifications: Notifications,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
@@ -21,12 +23,27 @@ impl Model {
             topic_user_id,
             topic_id,
             user_id,
+            notifications: Notifications::All,
             created_at: now,
             updated_at: now,
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "snake_case")]
+pub enum Notifications {
+    #[sea_orm(string_value = "all")]
+    All,
+    #[sea_orm(string_value = "roots")]
+    Roots,
+    #[sea_orm(string_value = "muted")]
+    Muted,
+    #[sea_orm(string_value = "digest")]
+    Digest,
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {}
 
@@ -1,16 +1,22 @@
 use async_nats::jetstream::Context;
-use bzd_messages_api::events::topic_user::Type;
-use sea_orm::DbConn;
+use bzd_messages_api::events::{topic::Type as TopicType, topic_user::Type};
+use chrono::{Duration, Utc};
+use sea_orm::{DbConn, TransactionTrait as _};
+use tracing::instrument;
 
 use crate::app::{
+    audit::{self, repo::AuditLogAction},
+    cache::CacheState,
     error::AppError,
+    metrics,
     topics::{
         events,
-        repo::{self, TopicModel, TopicUserModel},
+        repo::{self, TopicModel, TopicTransferModel, TopicTrendModel, TopicUserModel},
         settings::TopicsSettings,
     },
 };
 
+#[instrument(skip_all)]
 pub async fn create_topic(
     db: &DbConn,
     req: create_topic::Request,
@@ -18,12 +24,25 @@ pub async fn create_topic(
     let current_user = req.current_user.ok_or(AppError::Forbidden)?;
     let code = req.emoji.shortcode().ok_or(AppError::Validation)?.into();
 
+    let tx = db.begin().await?;
+
     let topic = repo::create_topic(
-        db,
+        &tx,
         TopicModel::new(current_user.user_id, req.emoji.to_string(), code),
     )
     .await?;
 
+    audit::record(
+        &tx,
+        Some(current_user),
+        AuditLogAction::Created,
+        None,
+        Some(&topic),
+    )
+    .await?;
+
+    tx.commit().await?;
+
     Ok(create_topic::Response { topic })
 }
 
@@ -41,11 +60,20 @@ pub mod create_topic {
     }
 }
 
+#[instrument(skip_all)]
 pub async fn get_topics(
     db: &DbConn,
+    cache: &CacheState,
     req: get_topics::Request,
 ) -> Result<get_topics::Response, AppError> {
-    let topics = repo::get_topics_by_ids(db, req.topic_ids).await?;
+    let topics = cache
+        .topics
+        .get_or_load(
+            req.topic_ids,
+            |it| it.topic_id,
+            |topic_ids| repo::get_topics_by_ids(db, topic_ids),
+        )
+        .await?;
 
     Ok(get_topics::Response { topics })
 }
@@ -64,6 +92,7 @@ pub mod get_topics {
     }
 }
 
+#[instrument(skip_all)]
 pub async fn get_topic(
     db: &DbConn,
     req: get_topic::Request,
@@ -140,6 +169,7 @@ pub mod get_topic {
     }
 }
 
+#[instrument(skip_all)]
 pub async fn get_user_topics(
     db: &DbConn,
     req: get_user_topics::Request,
@@ -163,6 +193,7 @@ pub mod get_user_topics {
     }
 }
 
+#[instrument(skip_all)]
 pub async fn get_user_topics_users(
     db: &DbConn,
     req: get_user_topics_users::Request,
@@ -190,6 +221,7 @@ pub mod get_user_topics_users {
     }
 }
 
+#[instrument(skip_all)]
 pub async fn create_topic_user(
     db: &DbConn,
     js: &Context,
@@ -204,12 +236,34 @@ pub async fn create_topic_user(
         return Err(AppError::Forbidden);
     }
 
+    if repo::find_user_block(db, topic.user_id, current_user.user_id)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::Forbidden);
+    }
+
+    let tx = db.begin().await?;
+
     let topic_user = repo::create_topic_user(
-        db,
+        &tx,
         TopicUserModel::new(current_user.user_id, topic.topic_id),
     )
     .await?;
 
+    audit::record(
+        &tx,
+        Some(current_user),
+        AuditLogAction::Created,
+        None,
+        Some(&topic_user),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    metrics::topic_user_created();
+
     // TODO: нужно сделать асинк отсылку (аутбокс??)
     events::topic_user(js, &settings.events, &topic_user, Type::Created).await?;
 
@@ -230,26 +284,226 @@ pub mod create_topic_user {
         pub topic_user: TopicUserModel,
     }
 
-    // #[cfg(test)]
-    // mod tests {
-    //     use bzd_lib::error::Error;
+    #[cfg(test)]
+    mod tests {
+        use bzd_lib::error::Error;
+        use chrono::Utc;
+        use sea_orm::{DatabaseBackend, MockDatabase};
+        use uuid::Uuid;
+
+        use crate::app::{
+            current_user::CurrentUser,
+            error::AppError,
+            mess::MessState,
+            topics::{
+                repo::{TopicModel, UserBlockModel},
+                service::{self, create_topic_user::Request},
+                settings::TopicsSettings,
+            },
+        };
+
+        #[tokio::test]
+        async fn test_forbidden_create_topic_user_own_topic() -> Result<(), Error> {
+            let settings = TopicsSettings::stub();
+            let mess = MessState::stub().await?;
+            let topic = TopicModel::stub();
+
+            let req = Request {
+                current_user: Some(CurrentUser {
+                    user_id: topic.user_id,
+                }),
+                topic_id: topic.topic_id,
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![topic.clone()]])
+                .into_connection();
+
+            let res = service::create_topic_user(&db, &mess.js, &settings, req).await;
+
+            assert!(matches!(res, Err(AppError::Forbidden)));
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_forbidden_create_topic_user_blocked() -> Result<(), Error> {
+            let settings = TopicsSettings::stub();
+            let mess = MessState::stub().await?;
+            let topic = TopicModel::stub();
+            let now = Utc::now().naive_utc();
+
+            // владелец топика заблокировал подписчика
+            let user_block = UserBlockModel {
+                user_block_id: Uuid::now_v7(),
+                user_id: topic.user_id,
+                blocked_user_id: Uuid::now_v7(),
+                created_at: now,
+                updated_at: now,
+            };
+
+            let req = Request {
+                current_user: Some(CurrentUser {
+                    user_id: user_block.blocked_user_id,
+                }),
+                topic_id: topic.topic_id,
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![topic.clone()]])
+                .append_query_results([vec![user_block]])
+                .into_connection();
+
+            let res = service::create_topic_user(&db, &mess.js, &settings, req).await;
+
+            assert!(matches!(res, Err(AppError::Forbidden)));
+
+            Ok(())
+        }
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn update_topic_user(
+    db: &DbConn,
+    js: &Context,
+    settings: &TopicsSettings,
+    req: update_topic_user::Request,
+) -> Result<(), AppError> {
+    let topic_user = save_topic_user(db, req).await?;
+
+    events::topic_user(js, &settings.events, &topic_user, Type::Updated).await
+}
+
+// Проверки и запись без публикации, чтобы тестироваться без брокера
+async fn save_topic_user(
+    db: &DbConn,
+    req: update_topic_user::Request,
+) -> Result<TopicUserModel, AppError> {
+    let current_user = req.current_user.ok_or(AppError::Forbidden)?;
+
+    let topic_user = repo::get_topic_user_by_id(db, req.topic_user_id).await?;
+
+    current_user.check_access(topic_user.user_id)?;
+
+    let tx = db.begin().await?;
+
+    let before = topic_user.clone();
+    let topic_user = repo::update_topic_user(&tx, topic_user, req.notifications).await?;
+
+    audit::record(
+        &tx,
+        Some(current_user),
+        AuditLogAction::Updated,
+        Some(&before),
+        Some(&topic_user),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(topic_user)
+}
+
+pub mod update_topic_user {
+    use uuid::Uuid;
+
+    use crate::app::{current_user::CurrentUser, topics::repo::TopicUserNotifications};
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+        pub topic_user_id: Uuid,
+        pub notifications: TopicUserNotifications,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bzd_lib::error::Error;
+        use bzd_messages_api::events::{Notifications, TopicUser, topic_user::Type};
+        use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+        use uuid::Uuid;
+
+        use crate::app::{
+            current_user::CurrentUser,
+            error::AppError,
+            events,
+            topics::{
+                events::topic_user_event,
+                repo::{TopicUserModel, TopicUserNotifications},
+                service::{self, update_topic_user::Request},
+                settings::TopicsSettings,
+            },
+        };
+
+        #[tokio::test]
+        async fn test_forbidden_update_topic_user() -> Result<(), Error> {
+            let topic_user = TopicUserModel::new(Uuid::now_v7(), Uuid::now_v7());
+
+            let req = Request {
+                current_user: Some(CurrentUser {
+                    user_id: Uuid::now_v7(),
+                }),
+                topic_user_id: topic_user.topic_user_id,
+                notifications: TopicUserNotifications::Muted,
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![topic_user.clone()]])
+                .into_connection();
+
+            let res = service::save_topic_user(&db, req).await;
+
+            assert!(matches!(res, Err(AppError::Forbidden)));
 
-    //     use crate::app::topics::service::{self, create_topic_user::Request};
+            Ok(())
+        }
 
-    //     #[tokio::test]
-    //     async fn test_ok_create_topic_user() -> Result<(), Error> {
-    //         let req = Request {
-    //             current_user: todo!(),
-    //             topic_id: todo!(),
-    //         };
+        #[tokio::test]
+        async fn test_ok_update_topic_user_event() -> Result<(), Error> {
+            let topic_user = TopicUserModel::new(Uuid::now_v7(), Uuid::now_v7());
+            let updated = TopicUserModel {
+                notifications: TopicUserNotifications::Digest,
+                ..topic_user.clone()
+            };
 
-    //         // let res = service::create_topic_user(db, js, settings, req).await?;
+            let req = Request {
+                current_user: Some(CurrentUser {
+                    user_id: topic_user.user_id,
+                }),
+                topic_user_id: topic_user.topic_user_id,
+                notifications: TopicUserNotifications::Digest,
+            };
 
-    //         Ok(())
-    //     }
-    // }
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![topic_user.clone()]])
+                .append_query_results([vec![updated.clone()]])
+                .append_exec_results([MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                }])
+                .into_connection();
+
+            let res = service::save_topic_user(&db, req).await?;
+
+            assert_eq!(res, updated);
+
+            let settings = TopicsSettings::stub();
+            let outgoing = topic_user_event(&settings.events, &res, Type::Updated)?;
+            let received = events::receive(Some(&outgoing.headers), &outgoing.payload)?
+                .ok_or(AppError::Unreachable)?;
+
+            assert_eq!(received.tp, Type::Updated.to_string());
+            assert_eq!(
+                received.decode::<TopicUser>()?.notifications,
+                Some(Notifications::Digest.into())
+            );
+
+            Ok(())
+        }
+    }
 }
 
+#[instrument(skip_all)]
 pub async fn delete_topic_user(
     db: &DbConn,
     js: &Context,
@@ -262,7 +516,22 @@ pub async fn delete_topic_user(
 
     current_user.check_access(topic_user.user_id)?;
 
-    repo::delete_topic_user(db, topic_user.clone()).await?;
+    let tx = db.begin().await?;
+
+    repo::delete_topic_user(&tx, topic_user.clone()).await?;
+
+    audit::record(
+        &tx,
+        Some(current_user),
+        AuditLogAction::Deleted,
+        Some(&topic_user),
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    metrics::topic_user_deleted();
 
     events::topic_user(js, &settings.events, &topic_user, Type::Deleted).await?;
 
@@ -280,6 +549,491 @@ pub mod delete_topic_user {
     }
 }
 
+#[instrument(skip_all)]
+pub async fn transfer_topic(
+    db: &DbConn,
+    req: transfer_topic::Request,
+) -> Result<transfer_topic::Response, AppError> {
+    let current_user = req.current_user.ok_or(AppError::Forbidden)?;
+
+    let topic = repo::get_topic_by_id(db, req.topic_id).await?;
+    current_user.check_access(topic.user_id)?;
+
+    if req.user_id == current_user.user_id {
+        return Err(AppError::Validation);
+    }
+
+    let tx = db.begin().await?;
+
+    let topic_transfer = repo::create_topic_transfer(
+        &tx,
+        TopicTransferModel::new(topic.topic_id, current_user.user_id, req.user_id),
+    )
+    .await?;
+
+    audit::record(
+        &tx,
+        Some(current_user),
+        AuditLogAction::Created,
+        None,
+        Some(&topic_transfer),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(transfer_topic::Response { topic_transfer })
+}
+
+pub mod transfer_topic {
+    use uuid::Uuid;
+
+    use crate::app::{current_user::CurrentUser, topics::repo::TopicTransferModel};
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+        pub topic_id: Uuid,
+        pub user_id: Uuid,
+    }
+
+    pub struct Response {
+        pub topic_transfer: TopicTransferModel,
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn accept_topic_transfer(
+    db: &DbConn,
+    js: &Context,
+    cache: &CacheState,
+    settings: &TopicsSettings,
+    req: accept_topic_transfer::Request,
+) -> Result<accept_topic_transfer::Response, AppError> {
+    let changes = save_topic_owner(db, req).await?;
+    let topic = changes.topic;
+
+    cache.topics.invalidate(topic.topic_id, topic.updated_at);
+
+    events::topic(js, &settings.events, &topic, TopicType::Updated).await?;
+
+    if let Some(topic_user) = &changes.topic_user {
+        events::topic_user(js, &settings.events, topic_user, Type::Deleted).await?;
+    }
+
+    Ok(accept_topic_transfer::Response { topic })
+}
+
+// Проверки и запись без публикации, как в save_topic_user
+async fn save_topic_owner(
+    db: &DbConn,
+    req: accept_topic_transfer::Request,
+) -> Result<accept_topic_transfer::Changes, AppError> {
+    let current_user = req.current_user.ok_or(AppError::Forbidden)?;
+
+    let tx = db.begin().await?;
+
+    let topic_transfer = repo::get_topic_transfer_by_id(&tx, req.topic_transfer_id).await?;
+    current_user.check_access(topic_transfer.to_user_id)?;
+
+    let topic = repo::get_topic_by_id(&tx, topic_transfer.topic_id).await?;
+
+    if topic.user_id != topic_transfer.user_id {
+        return Err(AppError::Forbidden);
+    }
+
+    // topics_code_user_id_udx: у получателя не может быть двух топиков с одним кодом
+    if repo::find_topic_by_code_and_user_id(&tx, topic.code.clone(), current_user.user_id)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::Conflict);
+    }
+
+    // владелец не может быть подписчиком своего топика
+    let topic_user = repo::find_topic_user(&tx, topic.topic_id, current_user.user_id).await?;
+
+    if let Some(topic_user) = &topic_user {
+        repo::delete_topic_user(&tx, topic_user.clone()).await?;
+
+        audit::record(
+            &tx,
+            Some(current_user),
+            AuditLogAction::Deleted,
+            Some(topic_user),
+            None,
+        )
+        .await?;
+    }
+
+    let before = topic.clone();
+    let topic = repo::update_topic_user_id(&tx, topic, current_user.user_id).await?;
+
+    audit::record(
+        &tx,
+        Some(current_user),
+        AuditLogAction::Updated,
+        Some(&before),
+        Some(&topic),
+    )
+    .await?;
+
+    repo::delete_topic_transfer(&tx, topic_transfer.clone()).await?;
+
+    audit::record(
+        &tx,
+        Some(current_user),
+        AuditLogAction::Deleted,
+        Some(&topic_transfer),
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(accept_topic_transfer::Changes { topic, topic_user })
+}
+
+pub mod accept_topic_transfer {
+    use uuid::Uuid;
+
+    use crate::app::{
+        current_user::CurrentUser,
+        topics::repo::{TopicModel, TopicUserModel},
+    };
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+        pub topic_transfer_id: Uuid,
+    }
+
+    pub struct Response {
+        pub topic: TopicModel,
+    }
+
+    pub struct Changes {
+        pub topic: TopicModel,
+        // подписка получателя, удаленная вместе с передачей
+        pub topic_user: Option<TopicUserModel>,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bzd_lib::error::Error;
+        use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+        use uuid::Uuid;
+
+        use crate::app::{
+            current_user::CurrentUser,
+            error::AppError,
+            topics::{
+                repo::{TopicModel, TopicTransferModel, TopicUserModel},
+                service::{self, accept_topic_transfer::Request},
+            },
+        };
+
+        fn exec() -> MockExecResult {
+            MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }
+        }
+
+        fn request(topic_transfer: &TopicTransferModel) -> Request {
+            Request {
+                current_user: Some(CurrentUser {
+                    user_id: topic_transfer.to_user_id,
+                }),
+                topic_transfer_id: topic_transfer.topic_transfer_id,
+            }
+        }
+
+        #[tokio::test]
+        async fn test_conflict_accept_topic_transfer_code() -> Result<(), Error> {
+            let topic = TopicModel::stub();
+            let topic_transfer =
+                TopicTransferModel::new(topic.topic_id, topic.user_id, Uuid::now_v7());
+
+            // у получателя уже есть топик с тем же кодом
+            let own_topic = TopicModel {
+                topic_id: Uuid::now_v7(),
+                user_id: topic_transfer.to_user_id,
+                ..topic.clone()
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![topic_transfer.clone()]])
+                .append_query_results([vec![topic.clone()]])
+                .append_query_results([vec![own_topic]])
+                .into_connection();
+
+            let res = service::save_topic_owner(&db, request(&topic_transfer)).await;
+
+            assert!(matches!(res, Err(AppError::Conflict)));
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_forbidden_accept_topic_transfer_stale_owner() -> Result<(), Error> {
+            let topic = TopicModel::stub();
+
+            // топик успел сменить владельца после создания передачи
+            let topic_transfer =
+                TopicTransferModel::new(topic.topic_id, Uuid::now_v7(), Uuid::now_v7());
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![topic_transfer.clone()]])
+                .append_query_results([vec![topic.clone()]])
+                .into_connection();
+
+            let res = service::save_topic_owner(&db, request(&topic_transfer)).await;
+
+            assert!(matches!(res, Err(AppError::Forbidden)));
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_ok_accept_topic_transfer_subscription() -> Result<(), Error> {
+            let topic = TopicModel::stub();
+            let topic_transfer =
+                TopicTransferModel::new(topic.topic_id, topic.user_id, Uuid::now_v7());
+            let topic_user = TopicUserModel::new(topic_transfer.to_user_id, topic.topic_id);
+            let updated = TopicModel {
+                user_id: topic_transfer.to_user_id,
+                ..topic.clone()
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![topic_transfer.clone()]])
+                .append_query_results([vec![topic.clone()]])
+                .append_query_results([Vec::<TopicModel>::new()])
+                .append_query_results([vec![topic_user.clone()]])
+                .append_exec_results([exec(), exec()])
+                .append_query_results([vec![updated.clone()]])
+                .append_exec_results([exec(), exec(), exec()])
+                .into_connection();
+
+            let res = service::save_topic_owner(&db, request(&topic_transfer)).await?;
+
+            assert_eq!(res.topic, updated);
+            assert_eq!(res.topic_user, Some(topic_user));
+
+            Ok(())
+        }
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn delete_topic_transfer(
+    db: &DbConn,
+    req: delete_topic_transfer::Request,
+) -> Result<(), AppError> {
+    let current_user = req.current_user.ok_or(AppError::Forbidden)?;
+
+    let tx = db.begin().await?;
+
+    let topic_transfer = repo::get_topic_transfer_by_id(&tx, req.topic_transfer_id).await?;
+
+    // отозвать может отправитель, отклонить - получатель
+    if current_user.check_access(topic_transfer.user_id).is_err() {
+        current_user.check_access(topic_transfer.to_user_id)?;
+    }
+
+    repo::delete_topic_transfer(&tx, topic_transfer.clone()).await?;
+
+    audit::record(
+        &tx,
+        Some(current_user),
+        AuditLogAction::Deleted,
+        Some(&topic_transfer),
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub mod delete_topic_transfer {
+    use uuid::Uuid;
+
+    use crate::app::current_user::CurrentUser;
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+        pub topic_transfer_id: Uuid,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bzd_lib::error::Error;
+        use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+        use uuid::Uuid;
+
+        use crate::app::{
+            current_user::CurrentUser,
+            error::AppError,
+            topics::{
+                repo::TopicTransferModel,
+                service::{self, delete_topic_transfer::Request},
+            },
+        };
+
+        #[tokio::test]
+        async fn test_ok_delete_topic_transfer() -> Result<(), Error> {
+            let topic_transfer =
+                TopicTransferModel::new(Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7());
+
+            for user_id in [topic_transfer.user_id, topic_transfer.to_user_id] {
+                let req = Request {
+                    current_user: Some(CurrentUser { user_id }),
+                    topic_transfer_id: topic_transfer.topic_transfer_id,
+                };
+
+                let db = MockDatabase::new(DatabaseBackend::Postgres)
+                    .append_query_results([vec![topic_transfer.clone()]])
+                    .append_exec_results([
+                        MockExecResult {
+                            last_insert_id: 0,
+                            rows_affected: 1,
+                        },
+                        MockExecResult {
+                            last_insert_id: 0,
+                            rows_affected: 1,
+                        },
+                    ])
+                    .into_connection();
+
+                service::delete_topic_transfer(&db, req).await?;
+            }
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_forbidden_delete_topic_transfer() -> Result<(), Error> {
+            let topic_transfer =
+                TopicTransferModel::new(Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7());
+
+            let req = Request {
+                current_user: Some(CurrentUser {
+                    user_id: Uuid::now_v7(),
+                }),
+                topic_transfer_id: topic_transfer.topic_transfer_id,
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([vec![topic_transfer]])
+                .into_connection();
+
+            let res = service::delete_topic_transfer(&db, req).await;
+
+            assert!(matches!(res, Err(AppError::Forbidden)));
+
+            Ok(())
+        }
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn get_user_topics_transfers(
+    db: &DbConn,
+    req: get_user_topics_transfers::Request,
+) -> Result<get_user_topics_transfers::Response, AppError> {
+    let current_user = req.current_user.ok_or(AppError::Forbidden)?;
+
+    let topics_transfers =
+        repo::get_topics_transfers_by_to_user_id(db, current_user.user_id).await?;
+
+    Ok(get_user_topics_transfers::Response { topics_transfers })
+}
+
+pub mod get_user_topics_transfers {
+    use crate::app::{current_user::CurrentUser, topics::repo::TopicTransferModel};
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+    }
+
+    pub struct Response {
+        pub topics_transfers: Vec<TopicTransferModel>,
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn get_trending_topics(
+    db: &DbConn,
+    settings: &TopicsSettings,
+) -> Result<get_trending_topics::Response, AppError> {
+    let window = i64::try_from(settings.trends.window).map_err(|_| AppError::Unreachable)?;
+    let activity_from = Utc::now().naive_utc() - Duration::seconds(window);
+
+    let topics = repo::get_trending_topics(db, activity_from, settings.trends.limit).await?;
+
+    Ok(get_trending_topics::Response { topics })
+}
+
+pub mod get_trending_topics {
+    use crate::app::topics::repo::TopicModel;
+
+    pub struct Response {
+        pub topics: Vec<TopicModel>,
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn create_topic_trend(
+    db: &DbConn,
+    settings: &TopicsSettings,
+    req: create_topic_trend::Request,
+) -> Result<(), AppError> {
+    repo::upsert_topic_trend(
+        db,
+        TopicTrendModel::new(
+            req.topic_id,
+            req.activity_at,
+            req.weight,
+            settings.trends.decay,
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+// Разовый пересчет по уже существующим messages_topics и topics_users, консьюмер видит только новые
+#[instrument(skip_all)]
+pub async fn backfill_topic_trends(
+    db: &DbConn,
+    settings: &TopicsSettings,
+) -> Result<u64, AppError> {
+    let window = i64::try_from(settings.trends.window).map_err(|_| AppError::Unreachable)?;
+    let activity_from = Utc::now().naive_utc() - Duration::seconds(window);
+    let weights = &settings.trends.weights;
+
+    repo::backfill_topics_trends(
+        db,
+        weights.message_topic,
+        weights.topic_user,
+        settings.trends.decay,
+        activity_from,
+    )
+    .await
+}
+
+pub mod create_topic_trend {
+    use chrono::NaiveDateTime;
+    use uuid::Uuid;
+
+    pub struct Request {
+        pub topic_id: Uuid,
+        pub activity_at: NaiveDateTime,
+        pub weight: f64,
+    }
+}
+
 pub fn get_emojis(settings: &TopicsSettings) -> Result<get_emojis::Response, AppError> {
     // очевидно что нужно убрать отсюда ресолв конфиги на каждый запрос
     let emojis = settings
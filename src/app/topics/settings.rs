@@ -1,9 +1,13 @@
 use serde::Deserialize;
 
+use crate::app::{events::settings::EventsMode, limiter::settings::RateLimitSettings};
+
 #[derive(Deserialize, Clone)]
 pub struct TopicsSettings {
     pub events: EventsSettings,
     pub emojis: EmojisSettings,
+    pub trends: TrendsSettings,
+    pub rate_limits: RateLimitsSettings,
 }
 
 #[derive(Deserialize, Clone)]
@@ -14,9 +18,76 @@ pub struct EmojisSettings {
 #[derive(Deserialize, Clone)]
 pub struct EventsSettings {
     pub topics_users: EventsTopicsUsersSettings,
+    pub topics: EventsTopicsSettings,
 }
 
 #[derive(Deserialize, Clone)]
 pub struct EventsTopicsUsersSettings {
     pub subject: String,
+    #[serde(default)]
+    pub mode: EventsMode,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct EventsTopicsSettings {
+    pub subject: String,
+    #[serde(default)]
+    pub mode: EventsMode,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct TrendsSettings {
+    pub window: u64,
+    pub decay: u64,
+    pub limit: u64,
+    pub subjects: Vec<String>,
+    pub weights: TrendsWeightsSettings,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct TrendsWeightsSettings {
+    pub message_topic: f64,
+    pub topic_user: f64,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct RateLimitsSettings {
+    pub create_topic_user: RateLimitSettings,
+}
+
+#[cfg(test)]
+impl TopicsSettings {
+    pub fn stub() -> Self {
+        Self {
+            events: EventsSettings {
+                topics_users: EventsTopicsUsersSettings {
+                    subject: "SUBJECT".into(),
+                    mode: EventsMode::Binary,
+                },
+                topics: EventsTopicsSettings {
+                    subject: "SUBJECT".into(),
+                    mode: EventsMode::Binary,
+                },
+            },
+            emojis: EmojisSettings {
+                list: vec!["👍".into()],
+            },
+            trends: TrendsSettings {
+                window: 1,
+                decay: 1,
+                limit: 2,
+                subjects: vec![],
+                weights: TrendsWeightsSettings {
+                    message_topic: 1.0,
+                    topic_user: 1.0,
+                },
+            },
+            rate_limits: RateLimitsSettings {
+                create_topic_user: RateLimitSettings {
+                    capacity: 2,
+                    period: 1,
+                },
+            },
+        }
+    }
 }
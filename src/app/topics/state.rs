@@ -1,8 +1,13 @@
-use crate::app::{db::DbState, mess::MessState, topics::settings::TopicsSettings};
+use crate::app::{
+    cache::CacheState, db::DbState, limiter::Limiter, mess::MessState,
+    topics::settings::TopicsSettings,
+};
 
 #[derive(Clone)]
 pub struct TopicsState {
     pub settings: TopicsSettings,
     pub db: DbState,
     pub mess: MessState,
+    pub limiter: Limiter,
+    pub cache: CacheState,
 }
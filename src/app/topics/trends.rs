@@ -0,0 +1,183 @@
+use std::time::Duration;
+
+use async_nats::jetstream::{AckKind, Message, consumer::pull};
+use bzd_lib::{error::Error, settings::NATSSettings};
+use futures::StreamExt as _;
+use sea_orm::DbConn;
+use tracing::{Instrument as _, error, info, info_span};
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+
+use crate::app::{
+    error::AppError,
+    shutdown::Shutdown,
+    telemetry,
+    topics::{service, settings::TopicsSettings, state::TopicsState},
+};
+
+// пауза перед повторной доставкой, чтобы не крутить эвент, пока база недоступна
+const REDELIVERY_DELAY: Duration = Duration::from_secs(5);
+
+pub enum Command {
+    Backfill,
+}
+
+// trends backfill
+impl TryFrom<&[String]> for Command {
+    type Error = AppError;
+
+    fn try_from(args: &[String]) -> Result<Self, Self::Error> {
+        match args.first().map(String::as_str) {
+            Some("backfill") => Ok(Self::Backfill),
+            _ => Err(AppError::Validation),
+        }
+    }
+}
+
+pub async fn run(db: &DbConn, settings: &TopicsSettings, command: Command) -> Result<(), Error> {
+    match command {
+        Command::Backfill => {
+            let count = service::backfill_topic_trends(db, settings).await?;
+
+            info!("trends: backfilled {} topics", count);
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn consumer(
+    state: &TopicsState,
+    settings: &NATSSettings,
+    shutdown: &Shutdown,
+) -> Result<(), Error> {
+    let name = format!("{}-trends", settings.consumer);
+
+    let consumer = state
+        .mess
+        .js
+        .get_stream(&settings.stream)
+        .await?
+        .get_or_create_consumer(
+            &name,
+            pull::Config {
+                durable_name: Some(name.clone()),
+                filter_subjects: state.settings.trends.subjects.clone(),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+    let mut messages = consumer.messages().await?;
+
+    info!("trends: consumer {} started", name);
+
+    loop {
+        // текущее сообщение дообрабатываем и акаем, новые после сигнала уже не берем
+        let message = tokio::select! {
+            message = messages.next() => message,
+            _ = shutdown.wait() => break,
+        };
+
+        let Some(message) = message else {
+            break;
+        };
+
+        // ошибки стрима и аков логируем и идем дальше, консьюмер не должен ронять сервер
+        let message = match message {
+            Ok(message) => message,
+            Err(err) => {
+                error!("trends: {}", err);
+                continue;
+            }
+        };
+
+        let span = info_span!(
+            "trends",
+            otel.name = message.subject.as_str(),
+            otel.kind = "consumer",
+        );
+        let _ = span.set_parent(telemetry::extract(message.headers.as_ref()));
+
+        let ack = handler(state, &message).instrument(span).await;
+
+        if let Err(err) = message.ack_with(ack).await {
+            error!("trends: {}", err);
+        }
+    }
+
+    info!("trends: consumer {} stopped", name);
+
+    Ok(())
+}
+
+// битый эвент повтор не исправит, а ошибку базы стоит переждать
+async fn handler(TopicsState { db, settings, .. }: &TopicsState, message: &Message) -> AckKind {
+    let req = match create_topic_trend::request(settings, message) {
+        Ok(Some(req)) => req,
+        Ok(None) => return AckKind::Ack,
+        Err(err) => {
+            error!("trends: {}", err);
+            return AckKind::Term;
+        }
+    };
+
+    if let Err(err) = service::create_topic_trend(&db.conn, settings, req).await {
+        error!("trends: {}", err);
+        return AckKind::Nak(Some(REDELIVERY_DELAY));
+    }
+
+    AckKind::Ack
+}
+
+mod create_topic_trend {
+    use async_nats::jetstream::Message;
+    use bzd_messages_api::events::{MessageTopic, TopicUser, message_topic, topic_user};
+    use chrono::{DateTime, NaiveDateTime, Utc};
+    use prost_types::Timestamp;
+
+    use crate::app::{
+        error::AppError,
+        events,
+        topics::{service::create_topic_trend::Request, settings::TopicsSettings},
+    };
+
+    pub fn request(
+        settings: &TopicsSettings,
+        message: &Message,
+    ) -> Result<Option<Request>, AppError> {
+        let Some(received) = events::receive(message.headers.as_ref(), &message.payload)? else {
+            return Ok(None);
+        };
+
+        let weights = &settings.trends.weights;
+
+        let req = if let Ok(message_topic::Type::Created) = received.tp.parse() {
+            let payload: MessageTopic = received.decode()?;
+
+            Request {
+                topic_id: payload.topic_id().parse()?,
+                activity_at: activity_at(payload.created_at),
+                weight: weights.message_topic,
+            }
+        } else if let Ok(topic_user::Type::Created) = received.tp.parse() {
+            let payload: TopicUser = received.decode()?;
+
+            Request {
+                topic_id: payload.topic_id().parse()?,
+                activity_at: activity_at(payload.created_at),
+                weight: weights.topic_user,
+            }
+        } else {
+            return Ok(None);
+        };
+
+        Ok(Some(req))
+    }
+
+    fn activity_at(created_at: Option<Timestamp>) -> NaiveDateTime {
+        created_at
+            .and_then(|it| DateTime::from_timestamp(it.seconds, 0))
+            .map(|it| it.naive_utc())
+            .unwrap_or_else(|| Utc::now().naive_utc())
+    }
+}
@@ -0,0 +1,18 @@
+use bzd_messages_api::users::users_service_server::UsersServiceServer;
+use tonic::service::interceptor::InterceptedService;
+
+use crate::app::{auth::AuthInterceptor, state::AppState, users::grpc::GrpcUsersService};
+
+mod grpc;
+pub mod repo;
+mod service;
+pub mod state;
+
+pub fn users_service(
+    state: &AppState,
+) -> InterceptedService<UsersServiceServer<GrpcUsersService>, AuthInterceptor> {
+    UsersServiceServer::with_interceptor(
+        GrpcUsersService::new(state.users.clone()),
+        state.auth.clone(),
+    )
+}
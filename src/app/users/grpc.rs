@@ -0,0 +1,185 @@
+use bzd_messages_api::users::{
+    BlockUserRequest, BlockUserResponse, GetBlockedUsersRequest, GetBlockedUsersResponse,
+    UnblockUserRequest, UnblockUserResponse, users_service_server::UsersService,
+};
+use tonic::{Request, Response, Status};
+
+use crate::app::{auth, users::state::UsersState};
+
+pub struct GrpcUsersService {
+    pub state: UsersState,
+}
+
+impl GrpcUsersService {
+    pub fn new(state: UsersState) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl UsersService for GrpcUsersService {
+    async fn block_user(
+        &self,
+        req: Request<BlockUserRequest>,
+    ) -> Result<Response<BlockUserResponse>, Status> {
+        let res = block_user::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(res))
+    }
+
+    async fn unblock_user(
+        &self,
+        req: Request<UnblockUserRequest>,
+    ) -> Result<Response<UnblockUserResponse>, Status> {
+        unblock_user::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(UnblockUserResponse::default()))
+    }
+
+    async fn get_blocked_users(
+        &self,
+        req: Request<GetBlockedUsersRequest>,
+    ) -> Result<Response<GetBlockedUsersResponse>, Status> {
+        let res = get_blocked_users::handler(&self.state, auth::into_inner(req)).await?;
+
+        Ok(Response::new(res))
+    }
+}
+
+mod block_user {
+    use bzd_messages_api::users::{BlockUserRequest, BlockUserResponse};
+
+    use crate::app::{
+        current_user::CurrentUser,
+        error::AppError,
+        users::{
+            service::{
+                self,
+                block_user::{Request, Response},
+            },
+            state::UsersState,
+        },
+    };
+
+    pub async fn handler(
+        UsersState { db }: &UsersState,
+        req: BlockUserRequest,
+    ) -> Result<BlockUserResponse, AppError> {
+        let res = service::block_user(&db.conn, req.try_into()?).await?;
+
+        Ok(res.into())
+    }
+
+    impl TryFrom<BlockUserRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: BlockUserRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                user_id: req.user_id().parse()?,
+            })
+        }
+    }
+
+    impl From<Response> for BlockUserResponse {
+        fn from(res: Response) -> Self {
+            Self {
+                user_block_id: Some(res.user_block.user_block_id.into()),
+            }
+        }
+    }
+}
+
+mod unblock_user {
+    use bzd_messages_api::users::UnblockUserRequest;
+
+    use crate::app::{
+        current_user::CurrentUser,
+        error::AppError,
+        users::{
+            service::{self, unblock_user::Request},
+            state::UsersState,
+        },
+    };
+
+    pub async fn handler(
+        UsersState { db }: &UsersState,
+        req: UnblockUserRequest,
+    ) -> Result<(), AppError> {
+        service::unblock_user(&db.conn, req.try_into()?).await?;
+
+        Ok(())
+    }
+
+    impl TryFrom<UnblockUserRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: UnblockUserRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+                user_id: req.user_id().parse()?,
+            })
+        }
+    }
+}
+
+mod get_blocked_users {
+    use bzd_messages_api::users::{
+        GetBlockedUsersRequest, GetBlockedUsersResponse, get_blocked_users_response,
+    };
+
+    use crate::app::{
+        current_user::CurrentUser,
+        error::AppError,
+        users::{
+            repo::UserBlockModel,
+            service::{
+                self,
+                get_blocked_users::{Request, Response},
+            },
+            state::UsersState,
+        },
+    };
+
+    pub async fn handler(
+        UsersState { db }: &UsersState,
+        req: GetBlockedUsersRequest,
+    ) -> Result<GetBlockedUsersResponse, AppError> {
+        let res = service::get_blocked_users(db.read_conn(), req.try_into()?).await?;
+
+        Ok(res.into())
+    }
+
+    impl TryFrom<GetBlockedUsersRequest> for Request {
+        type Error = AppError;
+
+        fn try_from(req: GetBlockedUsersRequest) -> Result<Self, Self::Error> {
+            Ok(Self {
+                current_user: CurrentUser::new(&req.current_user_id)?,
+            })
+        }
+    }
+
+    impl From<Response> for GetBlockedUsersResponse {
+        fn from(res: Response) -> Self {
+            Self {
+                user_ids: res
+                    .users_blocks
+                    .iter()
+                    .map(|it| it.blocked_user_id.into())
+                    .collect(),
+                users_blocks: res.users_blocks.iter().map(Into::into).collect(),
+            }
+        }
+    }
+
+    impl From<&UserBlockModel> for get_blocked_users_response::UserBlock {
+        fn from(user_block: &UserBlockModel) -> Self {
+            Self {
+                user_block_id: Some(user_block.user_block_id.into()),
+                user_id: Some(user_block.user_id.into()),
+                blocked_user_id: Some(user_block.blocked_user_id.into()),
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use sea_orm::{
+    ColumnTrait as _, ConnectionTrait, EntityTrait as _, IntoActiveModel as _, QueryFilter as _,
+    sea_query::OnConflict,
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app::error::AppError;
+
+mod user_block;
+
+pub type UserBlockModel = user_block::Model;
+
+#[instrument(skip_all)]
+pub async fn create_user_block<T: ConnectionTrait>(
+    db: &T,
+    model: UserBlockModel,
+) -> Result<UserBlockModel, AppError> {
+    user_block::Entity::insert(model.clone().into_active_model())
+        .on_conflict(
+            OnConflict::columns([
+                user_block::Column::UserId,
+                user_block::Column::BlockedUserId,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .do_nothing()
+        .exec(db)
+        .await?;
+
+    let user_block = user_block::Entity::find()
+        .filter(user_block::Column::UserId.eq(model.user_id))
+        .filter(user_block::Column::BlockedUserId.eq(model.blocked_user_id))
+        .one(db)
+        .await?
+        .ok_or(AppError::Unreachable)?;
+
+    Ok(user_block)
+}
+
+#[instrument(skip_all)]
+pub async fn delete_user_block<T: ConnectionTrait>(
+    db: &T,
+    user_id: Uuid,
+    blocked_user_id: Uuid,
+) -> Result<(), AppError> {
+    user_block::Entity::delete_many()
+        .filter(user_block::Column::UserId.eq(user_id))
+        .filter(user_block::Column::BlockedUserId.eq(blocked_user_id))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+#[instrument(skip_all)]
+pub async fn get_users_blocks_by_user_id<T: ConnectionTrait>(
+    db: &T,
+    user_id: Uuid,
+) -> Result<Vec<UserBlockModel>, AppError> {
+    let users_blocks = user_block::Entity::find()
+        .filter(user_block::Column::UserId.eq(user_id))
+        .all(db)
+        .await?;
+
+    Ok(users_blocks)
+}
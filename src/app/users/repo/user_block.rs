@@ -0,0 +1,33 @@
+use chrono::Utc;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "user_blocks")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_block_id: Uuid,
+    pub user_id: Uuid,
+    pub blocked_user_id: Uuid,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl Model {
+    pub fn new(user_id: Uuid, blocked_user_id: Uuid) -> Self {
+        let now = Utc::now().naive_utc();
+        let user_block_id = Uuid::now_v7();
+
+        Self {
+            user_block_id,
+            user_id,
+            blocked_user_id,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,114 @@
+use sea_orm::DbConn;
+use tracing::instrument;
+
+use crate::app::{
+    error::AppError,
+    users::repo::{self, UserBlockModel},
+};
+
+#[instrument(skip_all)]
+pub async fn block_user(
+    db: &DbConn,
+    req: block_user::Request,
+) -> Result<block_user::Response, AppError> {
+    let current_user = req.current_user.ok_or(AppError::Forbidden)?;
+
+    if current_user.user_id == req.user_id {
+        return Err(AppError::Validation);
+    }
+
+    let user_block =
+        repo::create_user_block(db, UserBlockModel::new(current_user.user_id, req.user_id)).await?;
+
+    Ok(block_user::Response { user_block })
+}
+
+pub mod block_user {
+    use uuid::Uuid;
+
+    use crate::app::{current_user::CurrentUser, users::repo::UserBlockModel};
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+        pub user_id: Uuid,
+    }
+
+    pub struct Response {
+        pub user_block: UserBlockModel,
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bzd_lib::error::Error;
+        use sea_orm::{DatabaseBackend, MockDatabase};
+        use uuid::Uuid;
+
+        use crate::app::{
+            current_user::CurrentUser,
+            error::AppError,
+            users::service::{self, block_user::Request},
+        };
+
+        #[tokio::test]
+        async fn test_self_block_user() -> Result<(), Error> {
+            let user_id = Uuid::now_v7();
+
+            let req = Request {
+                current_user: Some(CurrentUser { user_id }),
+                user_id,
+            };
+
+            let db = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+
+            let res = service::block_user(&db, req).await;
+
+            assert!(matches!(res, Err(AppError::Validation)));
+
+            Ok(())
+        }
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn unblock_user(db: &DbConn, req: unblock_user::Request) -> Result<(), AppError> {
+    let current_user = req.current_user.ok_or(AppError::Forbidden)?;
+
+    repo::delete_user_block(db, current_user.user_id, req.user_id).await?;
+
+    Ok(())
+}
+
+pub mod unblock_user {
+    use uuid::Uuid;
+
+    use crate::app::current_user::CurrentUser;
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+        pub user_id: Uuid,
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn get_blocked_users(
+    db: &DbConn,
+    req: get_blocked_users::Request,
+) -> Result<get_blocked_users::Response, AppError> {
+    let current_user = req.current_user.ok_or(AppError::Forbidden)?;
+
+    let users_blocks = repo::get_users_blocks_by_user_id(db, current_user.user_id).await?;
+
+    Ok(get_blocked_users::Response { users_blocks })
+}
+
+pub mod get_blocked_users {
+    use crate::app::{current_user::CurrentUser, users::repo::UserBlockModel};
+
+    pub struct Request {
+        pub current_user: Option<CurrentUser>,
+    }
+
+    pub struct Response {
+        pub users_blocks: Vec<UserBlockModel>,
+    }
+}
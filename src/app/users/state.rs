@@ -0,0 +1,6 @@
+use crate::app::db::DbState;
+
+#[derive(Clone)]
+pub struct UsersState {
+    pub db: DbState,
+}
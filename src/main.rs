@@ -4,9 +4,14 @@ mod app;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    bzd_lib::tracing::init()?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
 
-    app::run().await?;
+    match args.first().map(String::as_str) {
+        Some("migrate") => app::migrate(&args[1..]).await?,
+        Some("export") => app::export(&args[1..]).await?,
+        Some("trends") => app::trends(&args[1..]).await?,
+        _ => app::run().await?,
+    }
 
     Ok(())
 }